@@ -0,0 +1,143 @@
+use std::collections::HashMap;
+
+use crate::models::{
+    continent::{Continent, MapLevel, Region},
+    point::Size16,
+};
+
+// owner of the pixels no cell covers
+pub const NO_OWNER: u32 = u32::MAX;
+
+// two cells are neighbours when any of their pixels touch horizontally or vertically,
+// with `wrap_x` the last column also touches the first one
+pub fn build_adjacency(img_size: &Size16, owners: &[u32], cells_len: usize, wrap_x: bool) -> Vec<Vec<usize>> {
+    let mut adjacency: Vec<Vec<usize>> = vec![Vec::new(); cells_len];
+    let width = img_size.width as usize;
+    let height = img_size.height as usize;
+
+    for y in 0..height {
+        for x in 0..width {
+            let owner = owners[y * width + x];
+            if owner == NO_OWNER {
+                continue;
+            }
+
+            if x + 1 < width {
+                try_link(&mut adjacency, owner, owners[y * width + x + 1]);
+            } else if wrap_x && width > 1 {
+                try_link(&mut adjacency, owner, owners[y * width]);
+            }
+            if y + 1 < height {
+                try_link(&mut adjacency, owner, owners[(y + 1) * width + x]);
+            }
+        }
+    }
+
+    for neighbours in &mut adjacency {
+        neighbours.sort_unstable();
+        neighbours.dedup();
+    }
+
+    adjacency
+}
+
+fn try_link(adjacency: &mut [Vec<usize>], a: u32, b: u32) {
+    if b == NO_OWNER || a == b {
+        return;
+    }
+    adjacency[a as usize].push(b as usize);
+    adjacency[b as usize].push(a as usize);
+}
+
+// continents sorted by grid coord, so passes walking the hierarchy always visit regions in the same order
+pub fn get_sorted_continent_keys(continents: &HashMap<(u16, u16), Continent>) -> Vec<(u16, u16)> {
+    let mut keys: Vec<(u16, u16)> = continents.keys().copied().collect();
+    keys.sort_unstable();
+    keys
+}
+
+// regions in the order they are numbered by `build_regions_owner_raster`
+pub fn get_sorted_regions<'a>(
+    continents: &'a HashMap<(u16, u16), Continent>,
+    keys: &[(u16, u16)],
+) -> Vec<&'a Region> {
+    keys.iter()
+        .flat_map(|key| &continents[key].realms)
+        .flat_map(|rlm| &rlm.provinces)
+        .flat_map(|pv| &pv.regions)
+        .collect()
+}
+
+// regions are numbered in the order of `get_sorted_continent_keys` -> realms -> provinces -> regions
+pub fn build_regions_owner_raster(
+    img_size: &Size16,
+    continents: &HashMap<(u16, u16), Continent>,
+    keys: &[(u16, u16)],
+) -> (Vec<u32>, usize) {
+    build_owner_raster(img_size, continents, keys, MapLevel::Region)
+}
+
+// owner of every pixel at the given level, cells are numbered in the same walk as regions
+pub fn build_owner_raster(
+    img_size: &Size16,
+    continents: &HashMap<(u16, u16), Continent>,
+    keys: &[(u16, u16)],
+    level: MapLevel,
+) -> (Vec<u32>, usize) {
+    let mut owners: Vec<u32> = vec![NO_OWNER; img_size.width as usize * img_size.height as usize];
+    let mut i: u32 = 0;
+
+    for key in keys {
+        for rlm in &continents[key].realms {
+            for pv in &rlm.provinces {
+                for rg in &pv.regions {
+                    for px in &rg.pixels {
+                        owners[px.1 as usize * img_size.width as usize + px.0 as usize] = i;
+                    }
+                    if level == MapLevel::Region {
+                        i += 1;
+                    }
+                }
+                if level == MapLevel::Province {
+                    i += 1;
+                }
+            }
+            if level == MapLevel::Realm {
+                i += 1;
+            }
+        }
+        if level == MapLevel::Continent {
+            i += 1;
+        }
+    }
+
+    (owners, i as usize)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // cell 0 holds the west column, cell 1 the east one, cell 2 everything between
+    fn build_seam_owners() -> (Size16, Vec<u32>) {
+        let size = Size16::new(4, 2);
+        let owners = vec![0, 2, 2, 1, 0, 2, 2, 1];
+        (size, owners)
+    }
+
+    #[test]
+    fn cells_touching_only_across_the_seam_link_when_wrapping() {
+        let (size, owners) = build_seam_owners();
+
+        let adjacency = build_adjacency(&size, &owners, 3, true);
+        assert_eq!(adjacency, vec![vec![1, 2], vec![0, 2], vec![0, 1]]);
+    }
+
+    #[test]
+    fn cells_touching_only_across_the_seam_stay_apart_without_wrapping() {
+        let (size, owners) = build_seam_owners();
+
+        let adjacency = build_adjacency(&size, &owners, 3, false);
+        assert_eq!(adjacency, vec![vec![2], vec![2], vec![0, 1]]);
+    }
+}
@@ -21,3 +21,36 @@ pub fn deserialize_bin<T: serde::de::DeserializeOwned>(path: &str) -> T {
     }
     decoded.unwrap()
 }
+
+pub fn serialize_bin<T: serde::Serialize>(target: &T) -> Vec<u8> {
+    serialize(target).unwrap()
+}
+
+// None when the file is missing or was written for another Type
+pub fn try_deserialize_bin<T: serde::de::DeserializeOwned>(path: &str) -> Option<T> {
+    if !std::path::Path::new(path).exists() {
+        return None;
+    }
+    let data = file_read_write::read_bytes(path);
+    deserialize(&data).ok()
+}
+
+// the version goes in front of the data, so an outdated file can be told apart from a broken one
+pub fn write_versioned<T: serde::Serialize>(target: &T, version: u32, path: &str) {
+    let mut encoded: Vec<u8> = version.to_le_bytes().to_vec();
+    encoded.extend(serialize(target).unwrap());
+    file_read_write::write_bytes(encoded, path);
+}
+
+// the reason the file can't be read when it is missing, has another version or doesn't decode
+pub fn try_deserialize_versioned_bin<T: serde::de::DeserializeOwned>(path: &str, version: u32) -> Result<T, String> {
+    if !std::path::Path::new(path).exists() {
+        return Err(format!("{} does not exist", path));
+    }
+    let data = file_read_write::read_bytes(path);
+    let found = data.get(..4).map(|bytes| u32::from_le_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]));
+    if found != Some(version) {
+        return Err(format!("{} is not a version {} file", path, version));
+    }
+    deserialize(&data[4..]).map_err(|err| format!("{} could not be decoded: {}", path, err))
+}
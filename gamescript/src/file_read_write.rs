@@ -12,8 +12,9 @@ pub fn dir_name(lib_name: &str) -> Option<String> {
     return None;
 }
 
+// writes replace the whole file, opening an existing one would only give a read handle
 pub fn write_text(string: String, path: &str) {
-    let mut file = open_or_create(path);
+    let mut file = create_file(path).unwrap();
     file.write_all(&string.as_bytes());
 }
 
@@ -26,7 +27,7 @@ pub fn read_text(path: &str) -> String {
 }
 
 pub fn write_bytes(encoded: Vec<u8>, path: &str) {
-    let mut file = open_or_create(path);
+    let mut file = create_file(path).unwrap();
     file.write_all(&encoded);
 }
 
@@ -1,7 +1,10 @@
+pub mod adjacency;
 pub mod bin_read_write;
 pub mod file_read_write;
 pub mod json_read_write;
 pub mod models;
+pub mod pathfinding;
+pub mod province_graph;
 
 pub fn add(left: usize, right: usize) -> usize {
     left + right
@@ -0,0 +1,185 @@
+use serde::{Deserialize, Serialize};
+use super::color::Color8;
+
+#[derive(Serialize, Deserialize, PartialEq, Debug)]
+#[serde(default)]
+pub struct ClimateSettings {
+    pub build: bool,
+    // sea level temperatures in degrees celsius
+    pub equator_temperature: f32,
+    pub pole_temperature: f32,
+    // degrees lost between sea level and the highest elevation
+    pub lapse_rate: f32,
+    // yearly precipitation in millimetres under the wettest wind band
+    pub max_precipitation: f32,
+    // moisture winds pick up per pixel over the sea
+    pub evaporation: f32,
+    // share of the carried moisture that rains out per pixel over land
+    pub rainfall_rate: f32,
+    // extra rain when winds climb, this is what leaves a rain shadow behind mountains
+    pub orographic_factor: f32,
+}
+
+impl Default for ClimateSettings {
+    fn default() -> ClimateSettings {
+        ClimateSettings {
+            build: true,
+            equator_temperature: 30.0,
+            pole_temperature: -20.0,
+            lapse_rate: 20.0,
+            max_precipitation: 4000.0,
+            evaporation: 0.02,
+            rainfall_rate: 0.003,
+            orographic_factor: 3.0,
+        }
+    }
+}
+
+// Whittaker style biomes, classified from temperature and precipitation.
+#[derive(Serialize, Deserialize, PartialEq, Eq, Debug, Clone, Copy, Default)]
+pub enum Biome {
+    #[default]
+    Ocean,
+    Lake,
+    Ice,
+    Tundra,
+    Taiga,
+    ColdDesert,
+    TemperateGrassland,
+    Shrubland,
+    TemperateForest,
+    TemperateRainforest,
+    HotDesert,
+    Savanna,
+    TropicalSeasonalForest,
+    TropicalRainforest,
+}
+
+impl Biome {
+    pub const ALL: [Biome; 14] = [
+        Biome::Ocean,
+        Biome::Lake,
+        Biome::Ice,
+        Biome::Tundra,
+        Biome::Taiga,
+        Biome::ColdDesert,
+        Biome::TemperateGrassland,
+        Biome::Shrubland,
+        Biome::TemperateForest,
+        Biome::TemperateRainforest,
+        Biome::HotDesert,
+        Biome::Savanna,
+        Biome::TropicalSeasonalForest,
+        Biome::TropicalRainforest,
+    ];
+
+    // land biome for a temperature in celsius and a yearly precipitation in millimetres
+    pub fn classify(temperature: f32, precipitation: f32) -> Biome {
+        if temperature < -10.0 {
+            Biome::Ice
+        } else if temperature < 0.0 {
+            Biome::Tundra
+        } else if temperature < 7.0 {
+            if precipitation < 250.0 { Biome::ColdDesert } else { Biome::Taiga }
+        } else if temperature < 20.0 {
+            if precipitation < 250.0 {
+                Biome::ColdDesert
+            } else if precipitation < 600.0 {
+                Biome::TemperateGrassland
+            } else if precipitation < 1000.0 && temperature >= 14.0 {
+                Biome::Shrubland
+            } else if precipitation < 2200.0 {
+                Biome::TemperateForest
+            } else {
+                Biome::TemperateRainforest
+            }
+        } else if precipitation < 400.0 {
+            Biome::HotDesert
+        } else if precipitation < 1200.0 {
+            Biome::Savanna
+        } else if precipitation < 2500.0 {
+            Biome::TropicalSeasonalForest
+        } else {
+            Biome::TropicalRainforest
+        }
+    }
+
+    pub fn color(&self) -> Color8 {
+        match self {
+            Biome::Ocean => Color8::new(38, 70, 120),
+            Biome::Lake => Color8::new(70, 120, 170),
+            Biome::Ice => Color8::new(240, 248, 255),
+            Biome::Tundra => Color8::new(170, 180, 160),
+            Biome::Taiga => Color8::new(60, 100, 80),
+            Biome::ColdDesert => Color8::new(200, 190, 160),
+            Biome::TemperateGrassland => Color8::new(170, 190, 100),
+            Biome::Shrubland => Color8::new(160, 160, 90),
+            Biome::TemperateForest => Color8::new(70, 140, 60),
+            Biome::TemperateRainforest => Color8::new(40, 110, 70),
+            Biome::HotDesert => Color8::new(230, 200, 130),
+            Biome::Savanna => Color8::new(200, 180, 80),
+            Biome::TropicalSeasonalForest => Color8::new(110, 160, 50),
+            Biome::TropicalRainforest => Color8::new(20, 120, 40),
+        }
+    }
+
+    // how well a biome feeds a settlement, 0..1
+    pub fn habitability(&self) -> f32 {
+        match self {
+            Biome::Ocean | Biome::Lake | Biome::Ice => 0.0,
+            Biome::Tundra => 0.15,
+            Biome::Taiga => 0.4,
+            Biome::ColdDesert => 0.2,
+            Biome::TemperateGrassland => 0.9,
+            Biome::Shrubland => 0.7,
+            Biome::TemperateForest => 1.0,
+            Biome::TemperateRainforest => 0.7,
+            Biome::HotDesert => 0.15,
+            Biome::Savanna => 0.7,
+            Biome::TropicalSeasonalForest => 0.8,
+            Biome::TropicalRainforest => 0.5,
+        }
+    }
+
+    // standing timber a biome carries, 0..1
+    pub fn timber(&self) -> f32 {
+        match self {
+            Biome::Ocean | Biome::Lake | Biome::Ice | Biome::HotDesert | Biome::ColdDesert => 0.0,
+            Biome::Tundra => 0.05,
+            Biome::TemperateGrassland => 0.1,
+            Biome::Savanna => 0.2,
+            Biome::Shrubland => 0.3,
+            Biome::Taiga => 0.8,
+            Biome::TropicalSeasonalForest => 0.8,
+            Biome::TemperateForest => 1.0,
+            Biome::TemperateRainforest => 1.0,
+            Biome::TropicalRainforest => 0.9,
+        }
+    }
+
+    // how much harder a biome is to cross than open grassland, never below 1
+    pub fn travel_cost(&self) -> f32 {
+        match self {
+            Biome::Ocean | Biome::Lake | Biome::TemperateGrassland => 1.0,
+            Biome::Ice => 4.0,
+            Biome::Tundra | Biome::Taiga | Biome::HotDesert => 2.0,
+            Biome::ColdDesert => 1.5,
+            Biome::Shrubland => 1.2,
+            Biome::TemperateForest => 1.6,
+            Biome::TemperateRainforest => 2.5,
+            Biome::Savanna => 1.1,
+            Biome::TropicalSeasonalForest => 1.8,
+            Biome::TropicalRainforest => 3.0,
+        }
+    }
+}
+
+// Pixel share of every biome within a province or realm.
+#[derive(Serialize, Deserialize, PartialEq, Debug, Default)]
+pub struct BiomeSummary {
+    pub dominant: Biome,
+    // biomes present with their pixel count, most common first
+    pub pixels: Vec<(Biome, u32)>,
+    pub average_temperature: f32,
+    pub average_precipitation: f32,
+}
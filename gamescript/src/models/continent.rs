@@ -1,6 +1,6 @@
 use std::collections::HashMap;
 use serde::{Deserialize, Serialize};
-use super::point::{Point16, Size16};
+use super::{climate::{Biome, BiomeSummary, ClimateSettings}, erosion::ErosionSettings, export::ExportSettings, heightmap::{Heightmap, HeightmapSettings}, hydrology::{Hydrology, HydrologySettings}, naming::NamingSettings, noise::NoiseSettings, politics::{Politics, PoliticsSettings}, render::RenderSettings, resource::{ResourceSettings, Resources}, road::{Road, RoadSettings}, settlement::{Settlement, SettlementSettings}, point::{normalize_u8, Point16, Size16}, tectonics::{PlateBoundary, PlateSettings, PlateType, TectonicSettings}, vector::VectorSettings};
 
 #[derive(Serialize, Deserialize, PartialEq, Debug)]
 pub struct PlanetSettings {
@@ -16,21 +16,136 @@ pub struct PlanetSettings {
     pub province_cell_size: Size16,
     pub realm_cell_size: Size16,
     pub continent_cell_size: Size16,
+    #[serde(default)]
+    pub plate_settings: PlateSettings,
+    #[serde(default)]
+    pub tectonic_settings: TectonicSettings,
+    #[serde(default)]
+    pub seed: u64,
+    // low frequency relief mixed into every region
+    #[serde(default)]
+    pub region_noise: NoiseSettings,
+    // fine detail mixed into every pixel
+    #[serde(default = "NoiseSettings::detail")]
+    pub pixel_noise: NoiseSettings,
+    #[serde(default)]
+    pub heightmap_settings: HeightmapSettings,
+    #[serde(default)]
+    pub erosion_settings: ErosionSettings,
+    #[serde(default)]
+    pub hydrology_settings: HydrologySettings,
+    #[serde(default)]
+    pub climate_settings: ClimateSettings,
+    #[serde(default)]
+    pub vector_settings: VectorSettings,
+    #[serde(default)]
+    pub naming_settings: NamingSettings,
+    #[serde(default)]
+    pub politics_settings: PoliticsSettings,
+    #[serde(default)]
+    pub settlement_settings: SettlementSettings,
+    #[serde(default)]
+    pub road_settings: RoadSettings,
+    #[serde(default)]
+    pub resource_settings: ResourceSettings,
+    #[serde(default)]
+    pub render_settings: RenderSettings,
+    #[serde(default)]
+    pub export_settings: ExportSettings,
+}
+
+// Levels of the planet hierarchy, from the smallest cell up.
+#[derive(Serialize, Deserialize, PartialEq, Eq, Debug, Clone, Copy)]
+pub enum MapLevel {
+    Region,
+    Province,
+    Realm,
+    Continent,
+}
+
+impl MapLevel {
+    pub const ALL: [MapLevel; 4] = [MapLevel::Region, MapLevel::Province, MapLevel::Realm, MapLevel::Continent];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            MapLevel::Region => "region",
+            MapLevel::Province => "province",
+            MapLevel::Realm => "realm",
+            MapLevel::Continent => "continent",
+        }
+    }
 }
 
 #[derive(Serialize, Deserialize, PartialEq, Debug)]
 pub struct Planet {
     pub img_size: Size16,
-    pub continents: HashMap<(u16, u16), Continent>
+    pub continents: HashMap<(u16, u16), Continent>,
+    pub plate_boundaries: Vec<PlateBoundary>,
+    pub heightmap: Option<Heightmap>,
+    pub hydrology: Option<Hydrology>,
+    pub politics: Option<Politics>,
+    pub settlements: Vec<Settlement>,
+    pub roads: Vec<Road>,
+}
+
+impl Planet {
+    // written in front of planet.bin, bump when the layout of the planet changes
+    pub const VERSION: u32 = 1;
+
+    // grey value of a pixel, taken from the heightmap when the planet carries one
+    pub fn get_pixel_grey_value(&self, x: u16, y: u16, region_grey_value: u8) -> u8 {
+        match &self.heightmap {
+            Some(heightmap) => heightmap.get_u8(x, y),
+            None => region_grey_value,
+        }
+    }
+
+    // elevation at the region site, from the heightmap when the planet carries one
+    pub fn get_region_elevation(&self, rg: &Region) -> f32 {
+        match &self.heightmap {
+            Some(heightmap) => heightmap.get(
+                rg.site_point.x.min(heightmap.size.width - 1),
+                rg.site_point.y.min(heightmap.size.height - 1),
+            ),
+            None => normalize_u8(rg.grey_value as f64) as f32,
+        }
+    }
+
+    // elevation of every pixel in 0..1, from the heightmap or else the region values
+    pub fn get_pixel_elevations(&self) -> Vec<f32> {
+        if let Some(heightmap) = &self.heightmap {
+            return heightmap.values.clone();
+        }
+
+        let width = self.img_size.width as usize;
+        let mut elevations: Vec<f32> = vec![0.0; width * self.img_size.height as usize];
+        for continent in self.continents.values() {
+            for rlm in &continent.realms {
+                for pv in &rlm.provinces {
+                    for rg in &pv.regions {
+                        let elevation = self.get_region_elevation(rg);
+                        for px in &rg.pixels {
+                            elevations[px.1 as usize * width + px.0 as usize] = elevation;
+                        }
+                    }
+                }
+            }
+        }
+        elevations
+    }
 }
 
 #[derive(Serialize, Deserialize, PartialEq, Debug)]
 pub struct Continent {
+    pub name: String,
+    // naming style shared by the realms and provinces of the continent
+    pub name_style: String,
     pub grid_coord: Point16,
     pub site_point: Point16,
     pub top_right: Point16,
     pub bottom_left: Point16,
     pub plate_movement_direction: u8,
+    pub plate_type: PlateType,
     pub elevation: f32,
     pub is_empty: bool,
     pub realms: Vec<Realm>,
@@ -39,11 +154,14 @@ pub struct Continent {
 impl Continent {
     pub fn default() -> Continent {
         Continent {
+            name: String::new(),
+            name_style: String::new(),
             grid_coord: Point16::default(),
             site_point: Point16::default(),
             top_right: Point16::new(u16::MIN, u16::MIN),
             bottom_left: Point16::new(u16::MAX, u16::MAX),
             plate_movement_direction: 0,
+            plate_type: PlateType::Oceanic,
             elevation: 0.0,
             is_empty: false,
             realms: Vec::new()
@@ -52,11 +170,14 @@ impl Continent {
     
     pub fn new(grid_coord: Point16, site_point: Point16, plate_movement_direction: u8, elevation: f32) -> Continent {
         Continent {
+            name: String::new(),
+            name_style: String::new(),
             grid_coord,
             site_point,
             top_right: Point16::new(u16::MIN, u16::MIN),
             bottom_left: Point16::new(u16::MAX, u16::MAX),
             plate_movement_direction,
+            plate_type: PlateType::Oceanic,
             elevation,
             is_empty: false,
             realms: Vec::new()
@@ -66,22 +187,28 @@ impl Continent {
 
 #[derive(Serialize, Deserialize, PartialEq, Debug)]
 pub struct Realm {
+    pub name: String,
     pub grid_coord: Point16,
     pub site_point: Point16,
     pub top_right: Point16,
     pub bottom_left: Point16,
     pub average_grey_value: u8,
+    pub biome_summary: BiomeSummary,
+    pub resources: Resources,
     pub provinces: Vec<Province>,
 }
 
 impl Realm {
     pub fn new(grid_coord: Point16, site_point: Point16) -> Realm {
         Realm {
+            name: String::new(),
             grid_coord,
             site_point,
             top_right: Point16::new(u16::MIN, u16::MIN),
             bottom_left: Point16::new(u16::MAX, u16::MAX),
             average_grey_value: 0,
+            biome_summary: BiomeSummary::default(),
+            resources: Resources::default(),
             provinces: Vec::new()
         }
     }
@@ -89,22 +216,28 @@ impl Realm {
 
 #[derive(Serialize, Deserialize, PartialEq, Debug)]
 pub struct Province {
+    pub name: String,
     pub grid_coord: Point16,
     pub site_point: Point16,
     pub top_right: Point16,
     pub bottom_left: Point16,
     pub average_grey_value: u8,
+    pub biome_summary: BiomeSummary,
+    pub resources: Resources,
     pub regions: Vec<Region>,
 }
 
 impl Province {
     pub fn new(grid_coord: Point16, site_point: Point16) -> Province {
         Province {
+            name: String::new(),
             grid_coord,
             site_point,
             top_right: Point16::new(u16::MIN, u16::MIN),
             bottom_left: Point16::new(u16::MAX, u16::MAX),
             average_grey_value: 0,
+            biome_summary: BiomeSummary::default(),
+            resources: Resources::default(),
             regions: Vec::new()
         }
     }
@@ -116,6 +249,12 @@ pub struct Region {
     pub top_right: Point16,
     pub bottom_left: Point16,
     pub grey_value: u8,
+    // degrees celsius
+    pub temperature: f32,
+    // millimetres per year
+    pub precipitation: f32,
+    pub biome: Biome,
+    pub resources: Resources,
     pub pixels: Vec<(u16, u16)>,
 }
 
@@ -126,6 +265,10 @@ impl Region {
             top_right: Point16::default(),
             bottom_left: Point16::default(),
             grey_value: 0,
+            temperature: 0.0,
+            precipitation: 0.0,
+            biome: Biome::Ocean,
+            resources: Resources::default(),
             pixels: Vec::new()
         }
     }
@@ -0,0 +1,52 @@
+use serde::{Deserialize, Serialize};
+
+#[derive(Serialize, Deserialize, PartialEq, Debug)]
+#[serde(default)]
+pub struct ErosionSettings {
+    // needs the heightmap, erosion is skipped when heightmap_settings.build is off
+    pub build: bool,
+    // hydraulic erosion, every droplet runs downhill from a random pixel
+    pub droplets: u32,
+    pub droplet_lifetime: u16,
+    // 0 follows the slope right away, 1 keeps the previous direction
+    pub inertia: f32,
+    // sediment a droplet can carry per unit of speed, water and drop
+    pub capacity: f32,
+    pub min_capacity: f32,
+    // share of the surplus sediment dropped per step
+    pub deposition: f32,
+    // share of the free capacity taken from the terrain per step
+    pub erosion: f32,
+    // share of the water lost per step
+    pub evaporation: f32,
+    pub gravity: f32,
+    // pixels around a droplet it erodes from
+    pub erosion_radius: u8,
+    // thermal erosion, material slides down slopes steeper than the talus
+    pub thermal_iterations: u16,
+    // largest stable height difference between two neighbouring pixels
+    pub talus: f32,
+    // share of the excess moved per iteration
+    pub thermal_rate: f32,
+}
+
+impl Default for ErosionSettings {
+    fn default() -> ErosionSettings {
+        ErosionSettings {
+            build: true,
+            droplets: 150_000,
+            droplet_lifetime: 64,
+            inertia: 0.05,
+            capacity: 4.0,
+            min_capacity: 0.0001,
+            deposition: 0.3,
+            erosion: 0.3,
+            evaporation: 0.01,
+            gravity: 4.0,
+            erosion_radius: 3,
+            thermal_iterations: 8,
+            talus: 0.008,
+            thermal_rate: 0.5,
+        }
+    }
+}
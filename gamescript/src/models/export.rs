@@ -0,0 +1,100 @@
+use serde::{Deserialize, Serialize};
+
+use super::heightmap::EdgeMode;
+
+// How the heightmap is sampled when the export size differs from the planet size.
+#[derive(Serialize, Deserialize, PartialEq, Eq, Debug, Clone, Copy, Default)]
+pub enum Resampling {
+    Nearest,
+    #[default]
+    Bilinear,
+    // catmull-rom over the 4x4 pixels around the sample
+    Bicubic,
+}
+
+// Which way the green channel of a normal map points.
+#[derive(Serialize, Deserialize, PartialEq, Eq, Debug, Clone, Copy, Default)]
+pub enum NormalConvention {
+    // green up, towards the north of the map
+    #[default]
+    OpenGl,
+    // green down, towards the south of the map
+    DirectX,
+}
+
+// Heightmap files for terrain tools and game engines.
+#[derive(Serialize, Deserialize, PartialEq, Debug)]
+#[serde(default)]
+pub struct ExportSettings {
+    // size of the exported rasters, 0 keeps the planet size
+    pub width: u16,
+    pub height: u16,
+    pub resampling: Resampling,
+    // 16-bit greyscale PNG over the exported height range
+    pub png16: bool,
+    // little-endian u16 over the exported height range, without a header
+    pub r16: bool,
+    // little-endian f32 metres, without a header
+    pub r32: bool,
+    // metres below the sea level at elevation 0
+    pub sea_depth: f32,
+    // metres above the sea level at elevation 1
+    pub peak_height: f32,
+    // metres a planet pixel spans
+    pub pixel_size: f32,
+    // tangent-space RGB normal map
+    pub normal_map: bool,
+    // 16-bit greyscale PNGs, the slope over 0..90 degrees and the aspect, the way a slope faces,
+    // over 0..360 degrees clockwise from north
+    pub slope_map: bool,
+    // planet pixels the 0..1 elevation rises in the normal and slope maps
+    pub height_scale: f32,
+    pub edges: EdgeMode,
+    pub normal_convention: NormalConvention,
+}
+
+impl Default for ExportSettings {
+    fn default() -> ExportSettings {
+        ExportSettings {
+            width: 0,
+            height: 0,
+            resampling: Resampling::default(),
+            png16: true,
+            r16: true,
+            r32: true,
+            sea_depth: 6000.0,
+            peak_height: 6000.0,
+            pixel_size: 1000.0,
+            normal_map: true,
+            slope_map: true,
+            height_scale: 120.0,
+            edges: EdgeMode::WrapHorizontal,
+            normal_convention: NormalConvention::default(),
+        }
+    }
+}
+
+impl ExportSettings {
+    // metres above the sea level of a 0..1 elevation
+    pub fn get_metres(&self, elevation: f32, sea_level: f32) -> f32 {
+        if elevation < sea_level {
+            -(sea_level - elevation) / sea_level.max(f32::EPSILON) * self.sea_depth
+        } else {
+            (elevation - sea_level) / (1.0 - sea_level).max(f32::EPSILON) * self.peak_height
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn metres_put_the_sea_level_at_zero() {
+        let settings = ExportSettings { sea_depth: 4000.0, peak_height: 8000.0, ..ExportSettings::default() };
+        assert_eq!(settings.get_metres(0.4, 0.4), 0.0);
+        for (elevation, metres) in [(0.0, -4000.0), (0.2, -2000.0), (0.7, 4000.0), (1.0, 8000.0)] {
+            assert!((settings.get_metres(elevation, 0.4) - metres).abs() < 0.01, "{} at {}", metres, elevation);
+        }
+    }
+}
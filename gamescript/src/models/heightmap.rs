@@ -0,0 +1,240 @@
+use serde::{Deserialize, Serialize};
+use super::{export::Resampling, point::Size16};
+
+#[derive(Serialize, Deserialize, PartialEq, Debug)]
+#[serde(default)]
+pub struct HeightmapSettings {
+    pub build: bool,
+    // sites further than this many pixels don't weigh on a pixel
+    pub radius: f32,
+    // inverse distance power, higher values keep pixels closer to their own region
+    pub power: f32,
+}
+
+impl Default for HeightmapSettings {
+    fn default() -> HeightmapSettings {
+        HeightmapSettings {
+            build: true,
+            radius: 18.0,
+            power: 2.0,
+        }
+    }
+}
+
+// How the pixels along the map edges find their missing neighbours.
+#[derive(Serialize, Deserialize, PartialEq, Eq, Debug, Clone, Copy, Default)]
+pub enum EdgeMode {
+    // the edge pixel stands in for its missing neighbour
+    #[default]
+    Clamp,
+    // east and west meet like on the planet, north and south are clamped
+    WrapHorizontal,
+    // both axes wrap, for tiling textures
+    Wrap,
+}
+
+// Continuous elevation for every pixel of the planet, values are in 0..1.
+#[derive(Serialize, Deserialize, PartialEq, Debug)]
+pub struct Heightmap {
+    pub size: Size16,
+    pub values: Vec<f32>,
+}
+
+impl Heightmap {
+    pub fn new(size: Size16) -> Heightmap {
+        let len = size.width as usize * size.height as usize;
+        Heightmap { size, values: vec![0.0; len] }
+    }
+
+    pub fn get(&self, x: u16, y: u16) -> f32 {
+        self.values[y as usize * self.size.width as usize + x as usize]
+    }
+
+    pub fn set(&mut self, x: u16, y: u16, value: f32) {
+        let width = self.size.width as usize;
+        self.values[y as usize * width + x as usize] = value;
+    }
+
+    pub fn get_u8(&self, x: u16, y: u16) -> u8 {
+        (self.get(x, y).clamp(0.0, 1.0) * u8::MAX as f32).round() as u8
+    }
+
+    pub fn get_u16(&self, x: u16, y: u16) -> u16 {
+        (self.get(x, y).clamp(0.0, 1.0) * u16::MAX as f32).round() as u16
+    }
+
+    // bilinear sample between pixel centers, coordinates are clamped to the map
+    pub fn sample(&self, x: f32, y: f32) -> f32 {
+        let max_x = (self.size.width - 1) as f32;
+        let max_y = (self.size.height - 1) as f32;
+        let (x, y) = (x.clamp(0.0, max_x), y.clamp(0.0, max_y));
+        let (x0, y0) = (x.floor(), y.floor());
+        let (tx, ty) = (x - x0, y - y0);
+        let (x0, y0) = (x0 as u16, y0 as u16);
+        let (x1, y1) = ((x0 + 1).min(max_x as u16), (y0 + 1).min(max_y as u16));
+
+        let top = self.get(x0, y0) * (1.0 - tx) + self.get(x1, y0) * tx;
+        let bottom = self.get(x0, y1) * (1.0 - tx) + self.get(x1, y1) * tx;
+        top * (1.0 - ty) + bottom * ty
+    }
+
+    // catmull-rom through the 4x4 pixels around the sample, coordinates are clamped to the map
+    // and the overshoot of the curve to 0..1
+    pub fn sample_cubic(&self, x: f32, y: f32) -> f32 {
+        let (x, y) = (x.clamp(0.0, (self.size.width - 1) as f32), y.clamp(0.0, (self.size.height - 1) as f32));
+        let (x0, y0) = (x.floor(), y.floor());
+        let (tx, ty) = (x - x0, y - y0);
+        let get = |dx: f32, dy: f32| {
+            let px = (x0 + dx).clamp(0.0, (self.size.width - 1) as f32) as u16;
+            let py = (y0 + dy).clamp(0.0, (self.size.height - 1) as f32) as u16;
+            self.get(px, py)
+        };
+        let cubic = |p: [f32; 4], t: f32| {
+            p[1] + 0.5
+                * t
+                * (p[2] - p[0] + t * (2.0 * p[0] - 5.0 * p[1] + 4.0 * p[2] - p[3] + t * (3.0 * (p[1] - p[2]) + p[3] - p[0])))
+        };
+        let rows: [f32; 4] = std::array::from_fn(|j| {
+            let dy = j as f32 - 1.0;
+            cubic([get(-1.0, dy), get(0.0, dy), get(1.0, dy), get(2.0, dy)], tx)
+        });
+        cubic(rows, ty).clamp(0.0, 1.0)
+    }
+
+    // the heightmap at another size, pixel centers of both sizes cover the same area
+    pub fn resize(&self, size: Size16, resampling: Resampling) -> Heightmap {
+        let (width, height) = (size.width, size.height);
+        let mut resized = Heightmap::new(size);
+        let scale_x = self.size.width as f32 / width as f32;
+        let scale_y = self.size.height as f32 / height as f32;
+        for y in 0..height {
+            for x in 0..width {
+                let (sx, sy) = ((x as f32 + 0.5) * scale_x - 0.5, (y as f32 + 0.5) * scale_y - 0.5);
+                let value = match resampling {
+                    Resampling::Nearest => {
+                        let px = sx.round().clamp(0.0, (self.size.width - 1) as f32) as u16;
+                        let py = sy.round().clamp(0.0, (self.size.height - 1) as f32) as u16;
+                        self.get(px, py)
+                    }
+                    Resampling::Bilinear => self.sample(sx, sy),
+                    Resampling::Bicubic => self.sample_cubic(sx, sy),
+                };
+                resized.set(x, y, value);
+            }
+        }
+        resized
+    }
+
+    // Unit surface normal from the central differences around the pixel, x to the east, y to the
+    // north and z up. The 0..1 elevation rises `height_scale` pixels, given along x and along y
+    // for maps whose pixels aren't square.
+    pub fn get_normal(&self, x: u16, y: u16, height_scale: [f32; 2], edges: EdgeMode) -> [f32; 3] {
+        let (wrap_x, wrap_y) = match edges {
+            EdgeMode::Clamp => (false, false),
+            EdgeMode::WrapHorizontal => (true, false),
+            EdgeMode::Wrap => (true, true),
+        };
+        let (left, right) = get_neighbours(x, self.size.width, wrap_x);
+        let (up, down) = get_neighbours(y, self.size.height, wrap_y);
+        let dx = (self.get(right.0, y) - self.get(left.0, y)) / (left.1 + right.1).max(1) as f32 * height_scale[0];
+        let dy = (self.get(x, up.0) - self.get(x, down.0)) / (up.1 + down.1).max(1) as f32 * height_scale[1];
+        let len = (dx * dx + dy * dy + 1.0).sqrt();
+        [-dx / len, -dy / len, 1.0 / len]
+    }
+
+    pub fn min_max(&self) -> (f32, f32) {
+        self.values
+            .iter()
+            .fold((f32::INFINITY, f32::NEG_INFINITY), |(min, max), &value| (min.min(value), max.max(value)))
+    }
+}
+
+// slope over 0..90 degrees and aspect, the way the slope faces, over 0..360 degrees clockwise
+// from north, of a normal from `get_normal`. Flat pixels face north.
+pub fn get_slope_and_aspect(normal: [f32; 3]) -> (f32, f32) {
+    let slope = normal[2].clamp(0.0, 1.0).acos().to_degrees();
+    // downhill is where the normal leans, the negative zeros of a flat normal would turn it south
+    let aspect = if normal[0] == 0.0 && normal[1] == 0.0 {
+        0.0
+    } else {
+        normal[0].atan2(normal[1]).to_degrees().rem_euclid(360.0)
+    };
+    (slope, aspect)
+}
+
+// the previous and next index along an axis, each with its distance from the index
+fn get_neighbours(i: u16, len: u16, wrap: bool) -> ((u16, u16), (u16, u16)) {
+    if wrap && len > 1 {
+        let (i, len) = (i as u32, len as u32);
+        return ((((i + len - 1) % len) as u16, 1), (((i + 1) % len) as u16, 1));
+    }
+    let (previous, next) = (i.saturating_sub(1), (i + 1).min(len - 1));
+    ((previous, i - previous), (next, next - i))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn resize_keeps_the_corner_values() {
+        let mut heightmap = Heightmap::new(Size16::new(3, 2));
+        heightmap.values = vec![0.1, 0.5, 0.2, 0.9, 0.3, 0.7];
+        let corners = |heightmap: &Heightmap| {
+            let (w, h) = (heightmap.size.width - 1, heightmap.size.height - 1);
+            [heightmap.get(0, 0), heightmap.get(w, 0), heightmap.get(0, h), heightmap.get(w, h)]
+        };
+
+        for resampling in [Resampling::Nearest, Resampling::Bilinear, Resampling::Bicubic] {
+            let resized = heightmap.resize(Size16::new(7, 5), resampling);
+            assert_eq!(resized.size, Size16::new(7, 5));
+            assert_eq!(corners(&resized), corners(&heightmap), "{:?}", resampling);
+        }
+    }
+
+    #[test]
+    fn tilted_plane_faces_downhill() {
+        // rises 0.01 per pixel to the east, with a height scale of 10 the slope is 0.1
+        let mut heightmap = Heightmap::new(Size16::new(5, 4));
+        for y in 0..4 {
+            for x in 0..5 {
+                heightmap.set(x, y, x as f32 * 0.01);
+            }
+        }
+        let normal = heightmap.get_normal(2, 1, [10.0; 2], EdgeMode::Clamp);
+        let len = 1.01_f32.sqrt();
+        for (value, expected) in normal.iter().zip([-0.1 / len, 0.0, 1.0 / len]) {
+            assert!((value - expected).abs() < 1e-5, "{:?}", normal);
+        }
+        let (slope, aspect) = get_slope_and_aspect(normal);
+        assert!((slope - 0.1_f32.atan().to_degrees()).abs() < 1e-3);
+        assert!((aspect - 270.0).abs() < 1e-3);
+
+        // rising to the north, y grows to the south, so it faces south
+        for y in 0..4 {
+            for x in 0..5 {
+                heightmap.set(x, y, (3 - y) as f32 * 0.01);
+            }
+        }
+        let (_, aspect) = get_slope_and_aspect(heightmap.get_normal(2, 1, [10.0; 2], EdgeMode::Clamp));
+        assert!((aspect - 180.0).abs() < 1e-3);
+        let flat = Heightmap::new(Size16::new(3, 3)).get_normal(1, 1, [10.0; 2], EdgeMode::Clamp);
+        assert_eq!(get_slope_and_aspect(flat), (0.0, 0.0));
+    }
+
+    #[test]
+    fn wrap_and_clamp_differ_at_the_edge() {
+        assert_eq!(get_neighbours(0, 4, false), ((0, 0), (1, 1)));
+        assert_eq!(get_neighbours(0, 4, true), ((3, 1), (1, 1)));
+        assert_eq!(get_neighbours(3, 4, true), ((2, 1), (0, 1)));
+        assert_eq!(get_neighbours(u16::MAX - 1, u16::MAX, true), ((u16::MAX - 2, 1), (0, 1)));
+
+        // the east edge is high, only wrapping sees it from x = 0
+        let mut heightmap = Heightmap::new(Size16::new(4, 1));
+        heightmap.values = vec![0.0, 0.0, 0.0, 1.0];
+        assert_eq!(heightmap.get_normal(0, 0, [1.0; 2], EdgeMode::Clamp), [0.0, 0.0, 1.0]);
+        let wrapped = heightmap.get_normal(0, 0, [1.0; 2], EdgeMode::WrapHorizontal);
+        assert!(wrapped[0] > 0.0, "{:?}", wrapped);
+        assert_eq!(heightmap.get_normal(0, 0, [1.0; 2], EdgeMode::Wrap), wrapped);
+    }
+}
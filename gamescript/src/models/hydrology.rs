@@ -0,0 +1,55 @@
+use serde::{Deserialize, Serialize};
+
+#[derive(Serialize, Deserialize, PartialEq, Debug)]
+#[serde(default)]
+pub struct HydrologySettings {
+    pub build: bool,
+    // water every region receives, scaled by its area relative to the average region
+    pub rainfall: f32,
+    // accumulated rainfall a region needs before it carries a river
+    pub discharge_threshold: f32,
+    // depressions filled deeper than this (0..1 elevation) become lakes
+    pub lake_min_depth: f32,
+}
+
+impl Default for HydrologySettings {
+    fn default() -> HydrologySettings {
+        HydrologySettings {
+            build: true,
+            rainfall: 1.0,
+            discharge_threshold: 40.0,
+            lake_min_depth: 0.015,
+        }
+    }
+}
+
+// A river segment of a single Strahler order, from its source or the confluence that raised
+// its order down to the region it flows into. Regions are numbered like the planet hierarchy
+// is walked: continents sorted by grid coord -> realms -> provinces -> regions.
+#[derive(Serialize, Deserialize, PartialEq, Debug)]
+pub struct River {
+    pub regions: Vec<u32>,
+    // pixel path through the region sites, in flow direction
+    pub pixels: Vec<(u16, u16)>,
+    pub strahler_order: u8,
+    // accumulated rainfall at the last region of the segment
+    pub discharge: f32,
+    // index of the river this segment joins, None when it reaches the sea
+    pub flows_into: Option<u32>,
+}
+
+#[derive(Serialize, Deserialize, PartialEq, Debug)]
+pub struct Lake {
+    pub regions: Vec<u32>,
+    // water level in 0..1 elevation
+    pub surface: f32,
+}
+
+#[derive(Serialize, Deserialize, PartialEq, Debug)]
+pub struct Hydrology {
+    // region each region drains into, None for the sea
+    pub flow_directions: Vec<Option<u32>>,
+    pub flow_accumulation: Vec<f32>,
+    pub rivers: Vec<River>,
+    pub lakes: Vec<Lake>,
+}
@@ -1,3 +1,17 @@
 pub mod color;
+pub mod climate;
 pub mod point;
 pub mod continent;
+pub mod erosion;
+pub mod export;
+pub mod heightmap;
+pub mod hydrology;
+pub mod naming;
+pub mod noise;
+pub mod politics;
+pub mod render;
+pub mod resource;
+pub mod road;
+pub mod settlement;
+pub mod tectonics;
+pub mod vector;
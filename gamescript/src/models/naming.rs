@@ -0,0 +1,77 @@
+use serde::{Deserialize, Serialize};
+
+#[derive(Serialize, Deserialize, PartialEq, Debug)]
+#[serde(default)]
+pub struct NamingSettings {
+    pub build: bool,
+    // letters the markov chain looks back on, higher stays closer to the word list
+    pub order: u8,
+    pub min_length: u8,
+    pub max_length: u8,
+    // every continent picks one style, its realms and provinces are named in the same style
+    pub styles: Vec<NameStyle>,
+}
+
+// A culture flavour, names are generated from the letter patterns of its words.
+#[derive(Serialize, Deserialize, PartialEq, Debug)]
+pub struct NameStyle {
+    pub name: String,
+    pub words: Vec<String>,
+}
+
+impl NameStyle {
+    pub fn new(name: &str, words: &[&str]) -> NameStyle {
+        NameStyle {
+            name: name.to_string(),
+            words: words.iter().map(|word| word.to_string()).collect(),
+        }
+    }
+}
+
+impl Default for NamingSettings {
+    fn default() -> NamingSettings {
+        NamingSettings {
+            build: true,
+            order: 2,
+            min_length: 4,
+            max_length: 10,
+            styles: vec![
+                NameStyle::new("nordic", &NORDIC_WORDS),
+                NameStyle::new("latin", &LATIN_WORDS),
+                NameStyle::new("steppe", &STEPPE_WORDS),
+                NameStyle::new("desert", &DESERT_WORDS),
+                NameStyle::new("isles", &ISLES_WORDS),
+            ],
+        }
+    }
+}
+
+const NORDIC_WORDS: [&str; 30] = [
+    "askeby", "bergen", "bjorvik", "dalarna", "eskild", "falun", "gotland", "halden", "hedmark", "jorvik",
+    "kalmar", "karlstad", "lofoten", "malmo", "narvik", "orkney", "ragnhild", "sigrun", "skagen", "solvang",
+    "svalbard", "tromsa", "trondheim", "ulfhild", "uppsala", "vastra", "vidar", "ystad", "helgeland", "thorsby",
+];
+
+const LATIN_WORDS: [&str; 30] = [
+    "aquila", "aurelia", "brundisium", "capua", "corvinus", "florentia", "galatia", "hispalis", "lavinium", "lucania",
+    "mantua", "massilia", "narbo", "ostia", "patavium", "ravenna", "salernum", "tarentum", "valeria", "verona",
+    "vindobona", "aemilia", "tusculum", "placentia", "cremona", "ancona", "arretium", "calabria", "umbria", "sabina",
+];
+
+const STEPPE_WORDS: [&str; 30] = [
+    "aktobe", "altan", "arslan", "batu", "borjigin", "chagan", "dzungar", "erdene", "karakorum", "kazan",
+    "khorasan", "kipchak", "mongke", "nogai", "ordos", "otrar", "sarai", "selenge", "temur", "tuvan",
+    "ulan", "urga", "yesugei", "zhetysu", "bukhara", "kashgar", "tarbagatai", "khovd", "orkhon", "bayan",
+];
+
+const DESERT_WORDS: [&str; 30] = [
+    "alamut", "aqaba", "basra", "dhofar", "faiyum", "ghazni", "hadramut", "jazira", "karim", "luxor",
+    "marib", "najd", "qatif", "rashid", "saba", "shiraz", "sidon", "sinai", "tabuk", "taima",
+    "yathrib", "zabid", "asir", "harran", "palmyra", "petra", "sabra", "zafar", "nizwa", "dumah",
+];
+
+const ISLES_WORDS: [&str; 30] = [
+    "aberdeen", "arran", "bracken", "carrick", "donegal", "dunmore", "eilean", "galway", "glenmore", "inverness",
+    "islay", "kerry", "kildare", "lismore", "morven", "mull", "rathlin", "skye", "strathmore", "tara",
+    "tiree", "ulster", "wexford", "kintyre", "lochaber", "connacht", "carlow", "ardmore", "barra", "lewis",
+];
@@ -0,0 +1,57 @@
+use serde::{Deserialize, Serialize};
+
+#[derive(Serialize, Deserialize, PartialEq, Debug, Clone, Copy, Default)]
+pub enum NoiseKind {
+    Value,
+    #[default]
+    Perlin,
+    Simplex,
+}
+
+#[derive(Serialize, Deserialize, PartialEq, Debug, Clone, Copy)]
+#[serde(default)]
+pub struct NoiseSettings {
+    pub kind: NoiseKind,
+    // cycles across the image width for the first octave
+    pub frequency: f32,
+    pub octaves: u8,
+    // frequency multiplier between octaves
+    pub lacunarity: f32,
+    // amplitude multiplier between octaves
+    pub gain: f32,
+    // how far in pixels the domain warp may push a sample, 0 disables it
+    pub warp_strength: f32,
+    pub warp_frequency: f32,
+    // makes the left and right edges of the map meet seamlessly
+    pub wrap_x: bool,
+    // how much of the grey value range the noise can add or take away
+    pub weight: f32,
+}
+
+impl Default for NoiseSettings {
+    fn default() -> NoiseSettings {
+        NoiseSettings {
+            kind: NoiseKind::Perlin,
+            frequency: 8.0,
+            octaves: 5,
+            lacunarity: 2.0,
+            gain: 0.5,
+            warp_strength: 24.0,
+            warp_frequency: 4.0,
+            wrap_x: true,
+            weight: 0.08,
+        }
+    }
+}
+
+impl NoiseSettings {
+    pub fn detail() -> NoiseSettings {
+        NoiseSettings {
+            frequency: 64.0,
+            octaves: 3,
+            warp_strength: 0.0,
+            weight: 0.04,
+            ..NoiseSettings::default()
+        }
+    }
+}
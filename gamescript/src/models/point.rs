@@ -115,18 +115,11 @@ pub fn calculate_distance(a: &Point16, b: &Point16) -> f32 {
     distance
 }
 
-pub fn calculate_pixel_pos(gradient_pos: &Point16, per_pixel_size: &(f64, f64)) -> Point16 {
-    let mut pixel_pos = Point16 {
-        x: (gradient_pos.x as f64 * per_pixel_size.0).floor() as u16,
-        y: (gradient_pos.y as f64 * per_pixel_size.1).floor() as u16,
-    };
-    if pixel_pos.x > 127 {
-        pixel_pos.x = 127;
-    }
-    if pixel_pos.y > 127 {
-        pixel_pos.y = 127;
-    }
-    pixel_pos
+// distance the shorter way around a map that wraps horizontally
+pub fn calculate_wrapped_distance(a: &Point16, b: &Point16, width: u16) -> f32 {
+    let x_diff = (b.x as f32 - a.x as f32).abs();
+    let y_diff = b.y as f32 - a.y as f32;
+    x_diff.min(width as f32 - x_diff).hypot(y_diff)
 }
 
 // TODO: USAGE
@@ -0,0 +1,57 @@
+use serde::{Deserialize, Serialize};
+
+#[derive(Serialize, Deserialize, PartialEq, Debug)]
+#[serde(default)]
+pub struct PoliticsSettings {
+    pub build: bool,
+    pub nation_count: u16,
+    // provinces with less land than this are sea, nations cross them but don't own them
+    pub min_land_share: f32,
+    // cost of entering a province, on top of 1 per step
+    pub elevation_cost: f32,
+    pub water_cost: f32,
+    pub mountain_cost: f32,
+    // 0..1 elevation above which a province counts as mountains
+    pub mountain_elevation: f32,
+    // nations stop growing past this cost, 0 lets them grow until the land runs out
+    pub max_cost: f32,
+    // spread of the nation strengths, strong nations pay less per step
+    pub strength_min: f32,
+    pub strength_max: f32,
+}
+
+impl Default for PoliticsSettings {
+    fn default() -> PoliticsSettings {
+        PoliticsSettings {
+            build: true,
+            nation_count: 24,
+            min_land_share: 0.5,
+            elevation_cost: 20.0,
+            water_cost: 6.0,
+            mountain_cost: 4.0,
+            mountain_elevation: 0.75,
+            max_cost: 0.0,
+            strength_min: 0.6,
+            strength_max: 1.4,
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, PartialEq, Debug)]
+pub struct Nation {
+    pub name: String,
+    // province the nation grew from
+    pub capital: u32,
+    pub color: [u8; 3],
+    pub strength: f32,
+    pub provinces: u32,
+}
+
+// Ownership layer over the provinces, numbered like the planet hierarchy is walked:
+// continents sorted by grid coord -> realms -> provinces.
+#[derive(Serialize, Deserialize, PartialEq, Debug)]
+pub struct Politics {
+    pub nations: Vec<Nation>,
+    // nation of every province, None for the sea and unclaimed land
+    pub province_nations: Vec<Option<u16>>,
+}
@@ -0,0 +1,335 @@
+use serde::{Deserialize, Serialize};
+
+use super::{continent::MapLevel, resource::Resource};
+
+// How a layer is mixed into the layers below it, before its opacity is applied.
+#[derive(Serialize, Deserialize, PartialEq, Eq, Debug, Clone, Copy, Default)]
+pub enum BlendMode {
+    #[default]
+    Normal,
+    Multiply,
+    Screen,
+    Overlay,
+    Add,
+}
+
+// Colours of the cells of a level, in the order the planet hierarchy is walked.
+#[derive(Serialize, Deserialize, PartialEq, Debug, Clone)]
+pub enum Palette {
+    // graph colouring over the cell adjacency, neighbours never share a colour
+    Categorical,
+    // repeated when there are more cells than colours
+    Fixed(Vec<[u8; 3]>),
+}
+
+// Values sampled per pixel for an attribute layer.
+#[derive(Serialize, Deserialize, PartialEq, Debug, Clone, Copy)]
+pub enum Attribute {
+    // 0..1 like the heightmap
+    Elevation,
+    // degrees celsius
+    Temperature,
+    // millimetres per year
+    Precipitation,
+    // 0..1 amount on the region
+    Resource(Resource),
+}
+
+impl Attribute {
+    pub fn name(&self) -> &'static str {
+        match self {
+            Attribute::Elevation => "elevation",
+            Attribute::Temperature => "temperature",
+            Attribute::Precipitation => "precipitation",
+            Attribute::Resource(resource) => resource.name(),
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, PartialEq, Eq, Debug, Clone, Copy)]
+pub enum MarkerSource {
+    Settlements,
+    Capitals,
+}
+
+// Cells a borders layer outlines.
+#[derive(Serialize, Deserialize, PartialEq, Eq, Debug, Clone, Copy)]
+pub enum BorderSource {
+    Level(MapLevel),
+    // the land every nation holds, the sea and unclaimed land belong to none
+    Nations,
+}
+
+impl From<MapLevel> for BorderSource {
+    fn from(level: MapLevel) -> BorderSource {
+        BorderSource::Level(level)
+    }
+}
+
+#[derive(Serialize, Deserialize, PartialEq, Debug, Clone)]
+pub enum LayerKind {
+    Fill { level: MapLevel, palette: Palette },
+    // colour stops sorted by value, values in between are interpolated and values below
+    // the first stop are left unpainted
+    Ramp { attribute: Attribute, stops: Vec<(f32, [u8; 3])> },
+    // outlines between the cells, `width` pixels wide straddling the edge
+    Borders { source: BorderSource, color: [u8; 3], width: u8 },
+    Markers { source: MarkerSource },
+    // every region in the colour of its biome
+    Biomes,
+    // the lake regions and the river lines, rivers get wider with their order
+    Lakes { color: [u8; 3] },
+    Rivers { color: [u8; 3] },
+    // lines along the region sites of every road
+    Roads { color: [u8; 3], shortcut_color: [u8; 3] },
+    // the land every nation holds in its colour
+    Nations,
+    // cell names centred on their sites
+    Labels { level: MapLevel, color: [u8; 3] },
+    // grey lambertian shading of the heightmap, white where the terrain faces the sun
+    Hillshade { relief: ReliefSettings },
+}
+
+#[derive(Serialize, Deserialize, PartialEq, Debug, Clone)]
+pub struct RenderLayer {
+    pub kind: LayerKind,
+    pub opacity: f32,
+    pub blend: BlendMode,
+    // explained in a strip below the map, for fills and ramps
+    pub legend: bool,
+}
+
+impl RenderLayer {
+    pub fn new(kind: LayerKind) -> RenderLayer {
+        RenderLayer {
+            kind,
+            opacity: 1.0,
+            blend: BlendMode::Normal,
+            legend: false,
+        }
+    }
+
+    pub fn with_legend(mut self) -> RenderLayer {
+        self.legend = true;
+        self
+    }
+
+    pub fn with_blend(mut self, opacity: f32, blend: BlendMode) -> RenderLayer {
+        self.opacity = opacity;
+        self.blend = blend;
+        self
+    }
+}
+
+#[derive(Serialize, Deserialize, PartialEq, Debug, Clone, Copy)]
+#[serde(default)]
+pub struct BorderStyle {
+    pub visible: bool,
+    // pixels
+    pub width: u8,
+    pub color: [u8; 3],
+    pub opacity: f32,
+}
+
+impl Default for BorderStyle {
+    fn default() -> BorderStyle {
+        BorderStyle {
+            visible: true,
+            width: 1,
+            color: [40, 40, 40],
+            opacity: 1.0,
+        }
+    }
+}
+
+impl BorderStyle {
+    pub fn get_layer(&self, source: impl Into<BorderSource>) -> RenderLayer {
+        RenderLayer::new(LayerKind::Borders { source: source.into(), color: self.color, width: self.width })
+            .with_blend(self.opacity, BlendMode::Normal)
+    }
+}
+
+// Sun over the heightmap for the hillshade layer.
+#[derive(Serialize, Deserialize, PartialEq, Debug, Clone, Copy)]
+#[serde(default)]
+pub struct ReliefSettings {
+    // degrees clockwise from north, 315 lights the map from the top left
+    pub azimuth: f32,
+    // degrees above the horizon
+    pub altitude: f32,
+    // pixels the full 0..1 elevation range rises, higher values give steeper slopes
+    pub height_scale: f32,
+    // three more suns 45 degrees apart around the azimuth, so slopes facing along the
+    // light don't go flat
+    pub multi_directional: bool,
+    // how far the shading darkens the tint below it
+    pub strength: f32,
+}
+
+impl Default for ReliefSettings {
+    fn default() -> ReliefSettings {
+        ReliefSettings {
+            azimuth: 315.0,
+            altitude: 45.0,
+            height_scale: 120.0,
+            multi_directional: false,
+            strength: 0.7,
+        }
+    }
+}
+
+impl ReliefSettings {
+    // multiplied over the layers below, flat land keeps the sine of the altitude
+    pub fn get_layer(&self) -> RenderLayer {
+        RenderLayer::new(LayerKind::Hillshade { relief: *self }).with_blend(self.strength, BlendMode::Multiply)
+    }
+
+    // unit vectors towards the suns with their weights, x to the east, y to the north, z up
+    pub fn get_lights(&self) -> Vec<([f32; 3], f32)> {
+        let offsets: &[f32] = if self.multi_directional { &[-90.0, -45.0, 0.0, 45.0] } else { &[0.0] };
+        let altitude = self.altitude.to_radians();
+        offsets
+            .iter()
+            .map(|offset| {
+                let azimuth = (self.azimuth + offset).to_radians();
+                let direction = [azimuth.sin() * altitude.cos(), azimuth.cos() * altitude.cos(), altitude.sin()];
+                (direction, 1.0 / offsets.len() as f32)
+            })
+            .collect()
+    }
+
+    // 0..1 light on a surface with the given unit normal
+    pub fn get_shade(&self, normal: [f32; 3], lights: &[([f32; 3], f32)]) -> f32 {
+        lights
+            .iter()
+            .map(|(light, weight)| {
+                let lambert = normal[0] * light[0] + normal[1] * light[1] + normal[2] * light[2];
+                lambert.max(0.0) * weight
+            })
+            .sum::<f32>()
+            .clamp(0.0, 1.0)
+    }
+}
+
+#[derive(Serialize, Deserialize, PartialEq, Debug)]
+#[serde(default)]
+pub struct RenderSettings {
+    pub regions: BorderStyle,
+    pub provinces: BorderStyle,
+    pub realms: BorderStyle,
+    pub continents: BorderStyle,
+    // between the nations on the politics image and its overlay
+    pub nations: BorderStyle,
+    pub relief: ReliefSettings,
+}
+
+impl Default for RenderSettings {
+    fn default() -> RenderSettings {
+        RenderSettings {
+            regions: BorderStyle { visible: false, color: [150, 150, 150], opacity: 0.5, ..BorderStyle::default() },
+            provinces: BorderStyle { color: [90, 90, 90], opacity: 0.6, ..BorderStyle::default() },
+            realms: BorderStyle { width: 2, color: [40, 40, 40], ..BorderStyle::default() },
+            continents: BorderStyle { width: 3, color: [10, 10, 10], ..BorderStyle::default() },
+            nations: BorderStyle { color: [40, 30, 30], ..BorderStyle::default() },
+            relief: ReliefSettings::default(),
+        }
+    }
+}
+
+impl RenderSettings {
+    pub fn get_border(&self, level: MapLevel) -> &BorderStyle {
+        match level {
+            MapLevel::Region => &self.regions,
+            MapLevel::Province => &self.provinces,
+            MapLevel::Realm => &self.realms,
+            MapLevel::Continent => &self.continents,
+        }
+    }
+
+    // visible borders from the continents down, so the thin ones end up on top of the thick ones
+    pub fn get_border_layers(&self) -> Vec<RenderLayer> {
+        MapLevel::ALL
+            .iter()
+            .rev()
+            .filter(|&&level| self.get_border(level).visible)
+            .map(|&level| self.get_border(level).get_layer(level))
+            .collect()
+    }
+}
+
+// black to white over the 0..1 elevation
+pub fn get_grey_ramp() -> Vec<(f32, [u8; 3])> {
+    vec![(0.0, [0, 0, 0]), (1.0, [255, 255, 255])]
+}
+
+// grey relief over the land with a flat blue sea below the sea level
+pub fn get_sea_and_grey_ramp(sea_level: f32) -> Vec<(f32, [u8; 3])> {
+    let sea = [38, 70, 120];
+    let grey = (sea_level.clamp(0.0, 1.0) * 255.0).round() as u8;
+    vec![(0.0, sea), ((sea_level - SHORE).max(0.0), sea), (sea_level, [grey; 3]), (1.0, [255, 255, 255])]
+}
+
+// dark blue sea and dark grey land, a quiet base for a heatmap
+pub fn get_dim_ramp(sea_level: f32) -> Vec<(f32, [u8; 3])> {
+    let (sea, land) = ([20, 28, 48], [48, 48, 48]);
+    vec![(0.0, sea), ((sea_level - SHORE).max(0.0), sea), (sea_level, land), (1.0, land)]
+}
+
+// dark red through red and yellow to white over 0..1, nothing at all is left unpainted
+pub fn get_heat_ramp() -> Vec<(f32, [u8; 3])> {
+    vec![
+        (0.001, [64, 0, 0]),
+        (0.3, [236, 0, 0]),
+        (1.0 / 3.0, [255, 17, 0]),
+        (2.0 / 3.0, [255, 187, 0]),
+        (0.8, [255, 255, 102]),
+        (1.0, [255, 255, 255]),
+    ]
+}
+
+// the sea ramps end just below the sea level, so the coast is a sharp edge
+const SHORE: f32 = f32::EPSILON * 4.0;
+
+// sea colours by depth, as a share of the sea level
+const HYPSOMETRIC_SEA: [(f32, [u8; 3]); 3] = [
+    (0.0, [28, 101, 142]),
+    (0.7, [28, 101, 142]),
+    (1.0, [47, 137, 187]),
+];
+
+// land colours from the coast up, as a share of the height between the sea level and 1
+const HYPSOMETRIC_LAND: [(f32, [u8; 3]); 11] = [
+    (0.0, [142, 182, 134]),
+    (0.05, [121, 162, 92]),
+    (0.17, [138, 170, 96]),
+    (0.27, [167, 184, 101]),
+    (0.37, [184, 192, 105]),
+    (0.47, [239, 225, 123]),
+    (0.58, [223, 195, 113]),
+    (0.68, [202, 158, 100]),
+    (0.78, [166, 135, 113]),
+    (0.88, [230, 225, 220]),
+    (1.0, [255, 255, 255]),
+];
+
+// blues below the sea level, greens up to ochre mountains and white peaks above it
+pub fn get_hypsometric_ramp(sea_level: f32) -> Vec<(f32, [u8; 3])> {
+    let sea = HYPSOMETRIC_SEA.iter().map(|&(share, color)| ((share * sea_level - SHORE).max(0.0), color));
+    let land = HYPSOMETRIC_LAND.iter().map(|&(share, color)| (sea_level + share * (1.0 - sea_level), color));
+    sea.chain(land).collect()
+}
+
+// linear between the two stops around the value, clamped to the first and last
+pub fn sample_ramp(stops: &[(f32, [u8; 3])], value: f32) -> [u8; 3] {
+    let upper = stops.iter().position(|stop| stop.0 >= value);
+    let (from, to) = match upper {
+        Some(0) => (stops[0], stops[0]),
+        Some(upper) => (stops[upper - 1], stops[upper]),
+        None => match stops.last() {
+            Some(&last) => (last, last),
+            None => return [0, 0, 0],
+        },
+    };
+    let t = if to.0 > from.0 { (value - from.0) / (to.0 - from.0) } else { 0.0 };
+    std::array::from_fn(|c| (from.1[c] as f32 + (to.1[c] as f32 - from.1[c] as f32) * t).round() as u8)
+}
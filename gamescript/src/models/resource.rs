@@ -0,0 +1,102 @@
+use serde::{Deserialize, Serialize};
+
+use super::noise::NoiseSettings;
+
+#[derive(Serialize, Deserialize, PartialEq, Debug)]
+#[serde(default)]
+pub struct ResourceSettings {
+    pub build: bool,
+    // noise every resource is multiplied with, one seed per resource, so deposits come in patches
+    pub cluster_noise: NoiseSettings,
+    // 0..1 share of the noise range that holds nothing, higher values give smaller patches
+    pub cluster_threshold: f32,
+    // pixels from a plate boundary over which its ores fade out
+    pub boundary_distance: f32,
+    // ore richness of every boundary type, 0..1
+    pub convergent_metals: f32,
+    pub divergent_metals: f32,
+    pub transform_metals: f32,
+    // 0..1 elevation from which mountains carry metals and stone regardless of boundaries
+    pub mountain_elevation: f32,
+    // region steps from the coast over which fish and fertile river land fade to a half
+    pub fish_falloff: f32,
+    pub river_falloff: f32,
+    // amounts below this are dropped, so regions don't all hold a trace of everything
+    pub min_amount: f32,
+}
+
+impl Default for ResourceSettings {
+    fn default() -> ResourceSettings {
+        ResourceSettings {
+            build: true,
+            cluster_noise: NoiseSettings {
+                frequency: 24.0,
+                octaves: 3,
+                warp_strength: 8.0,
+                warp_frequency: 12.0,
+                weight: 1.0,
+                ..NoiseSettings::default()
+            },
+            cluster_threshold: 0.35,
+            boundary_distance: 40.0,
+            convergent_metals: 1.0,
+            divergent_metals: 0.6,
+            transform_metals: 0.3,
+            mountain_elevation: 0.75,
+            fish_falloff: 1.5,
+            river_falloff: 1.5,
+            min_amount: 0.05,
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, PartialEq, Eq, Debug, Clone, Copy)]
+pub enum Resource {
+    Metals,
+    Stone,
+    Timber,
+    FertileSoil,
+    Fish,
+}
+
+impl Resource {
+    pub const ALL: [Resource; 5] = [
+        Resource::Metals,
+        Resource::Stone,
+        Resource::Timber,
+        Resource::FertileSoil,
+        Resource::Fish,
+    ];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Resource::Metals => "metals",
+            Resource::Stone => "stone",
+            Resource::Timber => "timber",
+            Resource::FertileSoil => "fertile_soil",
+            Resource::Fish => "fish",
+        }
+    }
+}
+
+// Amount of every resource, 0..1 on a region, summed over the regions on provinces and realms.
+#[derive(Serialize, Deserialize, PartialEq, Debug, Clone, Copy, Default)]
+pub struct Resources {
+    pub amounts: [f32; 5],
+}
+
+impl Resources {
+    pub fn get(&self, resource: Resource) -> f32 {
+        self.amounts[resource as usize]
+    }
+
+    pub fn set(&mut self, resource: Resource, amount: f32) {
+        self.amounts[resource as usize] = amount;
+    }
+
+    pub fn add(&mut self, other: &Resources) {
+        for (amount, other) in self.amounts.iter_mut().zip(&other.amounts) {
+            *amount += other;
+        }
+    }
+}
@@ -0,0 +1,42 @@
+use serde::{Deserialize, Serialize};
+
+#[derive(Serialize, Deserialize, PartialEq, Debug)]
+#[serde(default)]
+pub struct RoadSettings {
+    pub build: bool,
+    // every settlement is offered a road to this many of its nearest neighbours
+    pub neighbours: u8,
+    // a non tree road is built when the network detour is this many times the direct route
+    pub shortcut_ratio: f32,
+    // cost multiplier per unit of 0..1 elevation climbed or descended between two sites
+    pub elevation_cost: f32,
+    // extra cost of bridging a river region
+    pub river_cost: f32,
+    // share of the cost paid on regions that already carry a road
+    pub reuse_factor: f32,
+}
+
+impl Default for RoadSettings {
+    fn default() -> RoadSettings {
+        RoadSettings {
+            build: true,
+            neighbours: 4,
+            shortcut_ratio: 1.6,
+            elevation_cost: 25.0,
+            river_cost: 8.0,
+            reuse_factor: 0.4,
+        }
+    }
+}
+
+// A road between two settlements along the region sites. Settlements are indices into
+// `Planet::settlements`, regions are numbered like the planet hierarchy is walked.
+#[derive(Serialize, Deserialize, PartialEq, Debug)]
+pub struct Road {
+    pub from: u32,
+    pub to: u32,
+    pub regions: Vec<u32>,
+    // roads added on top of the spanning tree to cut long detours
+    pub is_shortcut: bool,
+    pub cost: f32,
+}
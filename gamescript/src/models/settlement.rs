@@ -0,0 +1,77 @@
+use serde::{Deserialize, Serialize};
+
+#[derive(Serialize, Deserialize, PartialEq, Debug)]
+#[serde(default)]
+pub struct SettlementSettings {
+    pub build: bool,
+    pub cities: u16,
+    pub towns: u16,
+    pub villages: u16,
+    // smallest pixel distance between a new settlement of the kind and any placed one
+    pub city_spacing: f32,
+    pub town_spacing: f32,
+    pub village_spacing: f32,
+    // weights of the suitability terms, every term is in 0..1
+    pub elevation_weight: f32,
+    pub coast_weight: f32,
+    pub river_weight: f32,
+    pub flatness_weight: f32,
+    pub biome_weight: f32,
+    // 0..1 elevation above which nobody settles
+    pub max_elevation: f32,
+    // region steps over which the pull of the coast and rivers fades to a half
+    pub coast_falloff: f32,
+    pub river_falloff: f32,
+    // elevation difference to the neighbours that counts as fully rugged
+    pub ruggedness: f32,
+    // seeded noise added to the score so equal land doesn't settle in a grid
+    pub jitter: f32,
+}
+
+impl Default for SettlementSettings {
+    fn default() -> SettlementSettings {
+        SettlementSettings {
+            build: true,
+            cities: 40,
+            towns: 160,
+            villages: 600,
+            city_spacing: 80.0,
+            town_spacing: 30.0,
+            village_spacing: 12.0,
+            elevation_weight: 1.0,
+            coast_weight: 1.0,
+            river_weight: 1.2,
+            flatness_weight: 0.8,
+            biome_weight: 1.5,
+            max_elevation: 0.8,
+            coast_falloff: 2.0,
+            river_falloff: 1.5,
+            ruggedness: 0.08,
+            jitter: 0.3,
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, PartialEq, Eq, Debug, Clone, Copy)]
+pub enum SettlementKind {
+    City,
+    Town,
+    Village,
+}
+
+impl SettlementKind {
+    pub const ALL: [SettlementKind; 3] = [SettlementKind::City, SettlementKind::Town, SettlementKind::Village];
+}
+
+// A settlement at a region site. Regions, provinces and realms are numbered like the planet
+// hierarchy is walked: continents sorted by grid coord -> realms -> provinces -> regions.
+#[derive(Serialize, Deserialize, PartialEq, Debug)]
+pub struct Settlement {
+    pub name: String,
+    pub kind: SettlementKind,
+    pub position: (u16, u16),
+    pub region: u32,
+    pub province: u32,
+    pub realm: u32,
+    pub score: f32,
+}
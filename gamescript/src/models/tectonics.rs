@@ -0,0 +1,126 @@
+use serde::{Deserialize, Serialize};
+
+#[derive(Serialize, Deserialize, PartialEq, Debug, Clone, Copy, Default)]
+pub enum PlateGenerator {
+    /// One continent per `continent_cell_size` square, realms go to the nearest site.
+    #[default]
+    Grid,
+    /// Plates are seeded on random realms and grown over the realm adjacency graph.
+    FloodFill,
+}
+
+#[derive(Serialize, Deserialize, PartialEq, Debug, Clone, Copy, Default)]
+pub enum GradientProfile {
+    // light on the trailing edge of the plate, dark on the leading one
+    #[default]
+    Linear,
+    // dome shifted towards the trailing edge
+    Radial,
+    // light band across the plate, perpendicular to its direction
+    Ridge,
+    // linear with a wobbly falloff line
+    Perturbed { amplitude: f32, frequency: f32 },
+}
+
+#[derive(Serialize, Deserialize, PartialEq, Debug, Clone, Copy, Default)]
+pub enum PlateType {
+    #[default]
+    Oceanic,
+    Continental,
+}
+
+#[derive(Serialize, Deserialize, PartialEq, Debug)]
+#[serde(default)]
+pub struct PlateSettings {
+    pub generator: PlateGenerator,
+    // 0 means one plate per continent grid cell
+    pub plate_count: u16,
+    pub growth_rate_min: f32,
+    pub growth_rate_max: f32,
+    // share of the planet surface covered by continental plates
+    pub land_fraction: f32,
+    pub oceanic_elevation_min: f32,
+    pub oceanic_elevation_max: f32,
+    pub continental_elevation_min: f32,
+    pub continental_elevation_max: f32,
+    pub gradient_profile: GradientProfile,
+    // how much of the plate elevation the directional gradient can take away
+    pub gradient_tilt: f32,
+}
+
+impl Default for PlateSettings {
+    fn default() -> PlateSettings {
+        PlateSettings {
+            generator: PlateGenerator::Grid,
+            plate_count: 0,
+            growth_rate_min: 0.15,
+            growth_rate_max: 1.0,
+            land_fraction: 0.35,
+            oceanic_elevation_min: 0.1,
+            oceanic_elevation_max: 0.3,
+            continental_elevation_min: 0.5,
+            continental_elevation_max: 0.8,
+            gradient_profile: GradientProfile::Linear,
+            gradient_tilt: 0.35,
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, PartialEq, Debug)]
+#[serde(default)]
+pub struct TectonicSettings {
+    // grey value below which a region is under water
+    pub sea_level: f32,
+    // shear over normal motion above which a border is a transform boundary
+    pub transform_ratio: f32,
+    // continental collisions
+    pub mountain_height: f32,
+    // continental side of an oceanic plate diving under a continent
+    pub coastal_range_height: f32,
+    // overriding side of two oceanic plates converging
+    pub island_arc_height: f32,
+    pub trench_depth: f32,
+    pub rift_depth: f32,
+    pub ridge_height: f32,
+    // distance in pixels over which a boundary fades out
+    pub falloff_distance: f32,
+    // 1.0 fades linearly, higher values keep the effect closer to the border
+    pub falloff_power: f32,
+    pub blend: f32,
+}
+
+impl Default for TectonicSettings {
+    fn default() -> TectonicSettings {
+        TectonicSettings {
+            sea_level: 0.4,
+            transform_ratio: 1.5,
+            mountain_height: 0.35,
+            coastal_range_height: 0.25,
+            island_arc_height: 0.2,
+            trench_depth: 0.25,
+            rift_depth: 0.15,
+            ridge_height: 0.1,
+            falloff_distance: 48.0,
+            falloff_power: 1.5,
+            blend: 1.0,
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, PartialEq, Debug, Clone, Copy)]
+pub enum BoundaryType {
+    Convergent,
+    Divergent,
+    Transform,
+}
+
+#[derive(Serialize, Deserialize, PartialEq, Debug)]
+pub struct PlateBoundary {
+    pub plates: ((u16, u16), (u16, u16)),
+    pub boundary_type: BoundaryType,
+    // mean closing speed along the border, negative when the plates pull apart
+    pub convergence: f32,
+    pub shear: f32,
+    // number of region pairs touching across the border
+    pub length: u32,
+}
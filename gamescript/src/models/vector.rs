@@ -0,0 +1,60 @@
+use serde::{Deserialize, Serialize};
+
+#[derive(Serialize, Deserialize, PartialEq, Debug, Clone, Copy)]
+#[serde(default)]
+pub struct SvgLayer {
+    pub visible: bool,
+    // 0 leaves the shapes unfilled
+    pub fill_opacity: f32,
+    pub stroke_width: f32,
+    pub stroke: [u8; 3],
+}
+
+impl Default for SvgLayer {
+    fn default() -> SvgLayer {
+        SvgLayer {
+            visible: true,
+            fill_opacity: 0.0,
+            stroke_width: 1.0,
+            stroke: [40, 40, 40],
+        }
+    }
+}
+
+// Coordinates of exported GeoJSON, lon/lat maps the image as an equirectangular projection.
+#[derive(Serialize, Deserialize, PartialEq, Eq, Debug, Clone, Copy, Default)]
+pub enum GeoCoordinates {
+    Pixel,
+    #[default]
+    LonLat,
+}
+
+#[derive(Serialize, Deserialize, PartialEq, Debug)]
+#[serde(default)]
+pub struct VectorSettings {
+    // Douglas-Peucker tolerance in pixels
+    pub tolerance: f32,
+    // svg units per pixel
+    pub scale: f32,
+    pub coastline: SvgLayer,
+    pub regions: SvgLayer,
+    pub provinces: SvgLayer,
+    pub realms: SvgLayer,
+    pub continents: SvgLayer,
+    pub geojson_coordinates: GeoCoordinates,
+}
+
+impl Default for VectorSettings {
+    fn default() -> VectorSettings {
+        VectorSettings {
+            tolerance: 0.75,
+            scale: 1.0,
+            coastline: SvgLayer { fill_opacity: 1.0, stroke_width: 1.2, stroke: [30, 50, 90], ..SvgLayer::default() },
+            regions: SvgLayer { visible: false, fill_opacity: 1.0, stroke_width: 0.2, ..SvgLayer::default() },
+            provinces: SvgLayer { stroke_width: 0.3, stroke: [90, 90, 90], ..SvgLayer::default() },
+            realms: SvgLayer { fill_opacity: 0.35, stroke_width: 0.8, stroke: [60, 60, 60], ..SvgLayer::default() },
+            continents: SvgLayer { stroke_width: 1.6, stroke: [20, 20, 20], ..SvgLayer::default() },
+            geojson_coordinates: GeoCoordinates::default(),
+        }
+    }
+}
@@ -0,0 +1,134 @@
+use std::{cmp::Ordering, collections::BinaryHeap};
+
+// Graph search over adjacency lists, as built for regions or provinces. Costs come from
+// a closure returning None for steps that can't be taken, so callers decide what blocks.
+
+struct Open {
+    // cost so far plus the heuristic
+    estimate: f32,
+    node: usize,
+}
+
+impl PartialEq for Open {
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other) == Ordering::Equal
+    }
+}
+
+impl Eq for Open {}
+
+impl PartialOrd for Open {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for Open {
+    // reversed, so the BinaryHeap pops the cheapest node first, ties go to the lower node
+    fn cmp(&self, other: &Self) -> Ordering {
+        other
+            .estimate
+            .total_cmp(&self.estimate)
+            .then_with(|| other.node.cmp(&self.node))
+    }
+}
+
+// A* from `start` to `goal`. The heuristic must never overestimate the remaining cost,
+// return 0 for a plain Dijkstra. Returns the nodes from start to goal and the path cost.
+pub fn find_path<C, H>(adjacency: &[Vec<usize>], start: usize, goal: usize, cost: C, heuristic: H) -> Option<(Vec<usize>, f32)>
+where
+    C: Fn(usize, usize) -> Option<f32>,
+    H: Fn(usize) -> f32,
+{
+    let mut costs: Vec<f32> = vec![f32::INFINITY; adjacency.len()];
+    let mut previous: Vec<usize> = vec![usize::MAX; adjacency.len()];
+    let mut open: BinaryHeap<Open> = BinaryHeap::new();
+    costs[start] = 0.0;
+    open.push(Open { estimate: heuristic(start), node: start });
+
+    while let Some(Open { estimate, node }) = open.pop() {
+        if node == goal {
+            let mut path: Vec<usize> = vec![goal];
+            while *path.last().unwrap() != start {
+                path.push(previous[*path.last().unwrap()]);
+            }
+            path.reverse();
+            return Some((path, costs[goal]));
+        }
+        // stale entry, the node was reached cheaper since
+        if estimate > costs[node] + heuristic(node) {
+            continue;
+        }
+
+        for &next in &adjacency[node] {
+            let step = match cost(node, next) {
+                Some(step) => step,
+                None => continue,
+            };
+            let next_cost = costs[node] + step;
+            if next_cost < costs[next] {
+                costs[next] = next_cost;
+                previous[next] = node;
+                open.push(Open { estimate: next_cost + heuristic(next), node: next });
+            }
+        }
+    }
+
+    None
+}
+
+// Dijkstra from every source at once, the cost to reach each node or infinity. With a
+// budget the search stops there and nodes beyond it stay at infinity.
+pub fn find_costs<C>(adjacency: &[Vec<usize>], sources: &[usize], cost: C, budget: Option<f32>) -> Vec<f32>
+where
+    C: Fn(usize, usize) -> Option<f32>,
+{
+    let budget = budget.unwrap_or(f32::INFINITY);
+    let mut costs: Vec<f32> = vec![f32::INFINITY; adjacency.len()];
+    let mut open: BinaryHeap<Open> = BinaryHeap::new();
+    for &source in sources {
+        costs[source] = 0.0;
+        open.push(Open { estimate: 0.0, node: source });
+    }
+
+    while let Some(Open { estimate, node }) = open.pop() {
+        if estimate > costs[node] {
+            continue;
+        }
+        for &next in &adjacency[node] {
+            let step = match cost(node, next) {
+                Some(step) => step,
+                None => continue,
+            };
+            let next_cost = estimate + step;
+            if next_cost < costs[next] && next_cost <= budget {
+                costs[next] = next_cost;
+                open.push(Open { estimate: next_cost, node: next });
+            }
+        }
+    }
+
+    costs
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn path_goes_around_a_blocked_node() {
+        // 0 - 1 - 2
+        // |       |
+        // 3 - 4 - 5
+        let adjacency = vec![vec![1, 3], vec![0, 2], vec![1, 5], vec![0, 4], vec![3, 5], vec![2, 4]];
+        let cost = |_: usize, to: usize| if to == 1 { None } else { Some(1.0) };
+
+        let (path, total) = find_path(&adjacency, 0, 2, cost, |_| 0.0).unwrap();
+        assert_eq!(path, vec![0, 3, 4, 5, 2]);
+        assert_eq!(total, 4.0);
+        assert!(find_path(&adjacency, 0, 1, cost, |_| 0.0).is_none());
+
+        let costs = find_costs(&adjacency, &[0], cost, Some(2.0));
+        assert_eq!(costs, vec![0.0, f32::INFINITY, f32::INFINITY, 1.0, 2.0, f32::INFINITY]);
+    }
+}
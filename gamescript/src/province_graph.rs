@@ -0,0 +1,355 @@
+use crate::{
+    adjacency,
+    models::{
+        climate::Biome,
+        continent::{MapLevel, Planet},
+        point::{calculate_wrapped_distance, Point16},
+    },
+    pathfinding,
+};
+
+// Provinces are numbered like the planet hierarchy is walked: continents sorted by grid
+// coord -> realms -> provinces, the ids of `adjacency::build_owner_raster` that politics and
+// settlements use as well.
+
+#[derive(Debug)]
+pub struct ProvinceNode {
+    pub site_point: Point16,
+    // pixel weighted mean of the region elevations, 0..1
+    pub elevation: f32,
+    // share of the pixels above sea level
+    pub land_share: f32,
+    // at least `min_land_share` of the pixels are land
+    pub is_land: bool,
+    pub biome: Biome,
+    pub continent: u32,
+    pub realm: u32,
+}
+
+// What a step between two neighbouring provinces costs, None when it can't be taken.
+// `min_cost_per_distance` is the cheapest cost any step pays per pixel of site distance,
+// it keeps the A* heuristic from overestimating. Return 0 when that can't be bounded.
+pub trait MovementCost {
+    fn cost(&self, graph: &ProvinceGraph, from: usize, to: usize) -> Option<f32>;
+
+    fn min_cost_per_distance(&self) -> f32 {
+        0.0
+    }
+}
+
+impl<F> MovementCost for F
+where
+    F: Fn(&ProvinceGraph, usize, usize) -> Option<f32>,
+{
+    fn cost(&self, graph: &ProvinceGraph, from: usize, to: usize) -> Option<f32> {
+        self(graph, from, to)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum SeaCrossing {
+    Forbidden,
+    // cost per pixel of site distance for every step touching a sea province
+    Allowed { cost: f32 },
+}
+
+// Steps cost the distance between the sites, scaled up by the slope and the biome of the
+// province entered. Sea provinces are either closed or crossed at a flat rate.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct TerrainCost {
+    // cost multiplier per unit of 0..1 elevation climbed or descended
+    pub elevation_cost: f32,
+    pub sea: SeaCrossing,
+}
+
+impl Default for TerrainCost {
+    fn default() -> TerrainCost {
+        TerrainCost {
+            elevation_cost: 20.0,
+            sea: SeaCrossing::Forbidden,
+        }
+    }
+}
+
+impl MovementCost for TerrainCost {
+    fn cost(&self, graph: &ProvinceGraph, from: usize, to: usize) -> Option<f32> {
+        let (a, b) = (&graph.nodes[from], &graph.nodes[to]);
+        let distance = graph.get_distance(from, to);
+        if !a.is_land || !b.is_land {
+            return match self.sea {
+                SeaCrossing::Forbidden => None,
+                SeaCrossing::Allowed { cost } => Some(distance * cost),
+            };
+        }
+        let slope = (b.elevation - a.elevation).abs();
+        Some(distance * (1.0 + self.elevation_cost * slope) * b.biome.travel_cost())
+    }
+
+    fn min_cost_per_distance(&self) -> f32 {
+        match self.sea {
+            SeaCrossing::Forbidden => 1.0,
+            SeaCrossing::Allowed { cost } => cost.clamp(0.0, 1.0),
+        }
+    }
+}
+
+#[derive(Debug, PartialEq)]
+pub struct ProvincePath {
+    // from the start to the goal, both included
+    pub provinces: Vec<u32>,
+    pub cost: f32,
+}
+
+// Province adjacency of a planet for game side queries. Build it once per planet, every
+// query after that only walks the graph. The planet wraps horizontally, provinces on the
+// east and west edges are neighbours.
+pub struct ProvinceGraph {
+    pub nodes: Vec<ProvinceNode>,
+    pub adjacency: Vec<Vec<usize>>,
+    pub width: u16,
+}
+
+impl ProvinceGraph {
+    pub fn new(planet: &Planet, sea_level: f32, min_land_share: f32) -> ProvinceGraph {
+        let keys = adjacency::get_sorted_continent_keys(&planet.continents);
+        let (owners, provinces_len) =
+            adjacency::build_owner_raster(&planet.img_size, &planet.continents, &keys, MapLevel::Province);
+
+        let mut nodes: Vec<ProvinceNode> = Vec::with_capacity(provinces_len);
+        let mut realm: u32 = 0;
+        for (continent, key) in keys.iter().enumerate() {
+            for rlm in &planet.continents[key].realms {
+                for pv in &rlm.provinces {
+                    let (mut pixels, mut land_pixels, mut elevation_sum) = (0_u32, 0_u32, 0.0_f32);
+                    for rg in &pv.regions {
+                        let elevation = planet.get_region_elevation(rg);
+                        pixels += rg.pixels.len() as u32;
+                        elevation_sum += elevation * rg.pixels.len() as f32;
+                        if elevation >= sea_level {
+                            land_pixels += rg.pixels.len() as u32;
+                        }
+                    }
+                    let share = |value: f32| if pixels > 0 { value / pixels as f32 } else { 0.0 };
+                    let land_share = share(land_pixels as f32);
+                    nodes.push(ProvinceNode {
+                        site_point: Point16::new(pv.site_point.x, pv.site_point.y),
+                        elevation: share(elevation_sum),
+                        land_share,
+                        is_land: pixels > 0 && land_share >= min_land_share,
+                        biome: pv.biome_summary.dominant,
+                        continent: continent as u32,
+                        realm,
+                    });
+                }
+                realm += 1;
+            }
+        }
+
+        let adjacency = adjacency::build_adjacency(&planet.img_size, &owners, provinces_len, true);
+        ProvinceGraph { nodes, adjacency, width: planet.img_size.width }
+    }
+
+    pub fn len(&self) -> usize {
+        self.nodes.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.nodes.is_empty()
+    }
+
+    pub fn get_neighbours(&self, province: u32) -> impl Iterator<Item = u32> + '_ {
+        self.adjacency[province as usize].iter().map(|&n| n as u32)
+    }
+
+    // pixel distance between the province sites, the shorter way around the planet
+    pub fn get_distance(&self, from: usize, to: usize) -> f32 {
+        calculate_wrapped_distance(&self.nodes[from].site_point, &self.nodes[to].site_point, self.width)
+    }
+
+    // A* from one province to another, None when the goal can't be reached
+    pub fn find_path<C: MovementCost>(&self, from: u32, to: u32, movement: &C) -> Option<ProvincePath> {
+        let (from, to) = (from as usize, to as usize);
+        let min_cost = movement.min_cost_per_distance();
+        let cost = |a: usize, b: usize| movement.cost(self, a, b);
+        let heuristic = |node: usize| self.get_distance(node, to) * min_cost;
+        pathfinding::find_path(&self.adjacency, from, to, cost, heuristic).map(|(path, cost)| ProvincePath {
+            provinces: path.into_iter().map(|pv| pv as u32).collect(),
+            cost,
+        })
+    }
+
+    // every province reachable within the budget with its cost, cheapest first, ties by id
+    pub fn find_reachable<C: MovementCost>(&self, from: u32, budget: f32, movement: &C) -> Vec<(u32, f32)> {
+        let cost = |a: usize, b: usize| movement.cost(self, a, b);
+        let costs = pathfinding::find_costs(&self.adjacency, &[from as usize], cost, Some(budget));
+        let mut reachable: Vec<(u32, f32)> = costs
+            .into_iter()
+            .enumerate()
+            .filter(|(_, cost)| cost.is_finite())
+            .map(|(pv, cost)| (pv as u32, cost))
+            .collect();
+        reachable.sort_by(|a, b| a.1.total_cmp(&b.1).then(a.0.cmp(&b.0)));
+        reachable
+    }
+
+    // cost from the nearest source to every province, infinity where none reaches
+    pub fn build_distance_field<C: MovementCost>(&self, sources: &[u32], movement: &C) -> Vec<f32> {
+        let sources: Vec<usize> = sources.iter().map(|&pv| pv as usize).collect();
+        let cost = |a: usize, b: usize| movement.cost(self, a, b);
+        pathfinding::find_costs(&self.adjacency, &sources, cost, None)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::{
+        continent::{Continent, Province, Realm, Region},
+        point::Size16,
+    };
+    use std::collections::HashMap;
+
+    const LAND: u8 = 200;
+    const SEA: u8 = 20;
+    const SEA_LEVEL: f32 = 0.4;
+
+    // one continent and realm holding a grid of square provinces with one region each,
+    // the grey values are given row by row
+    fn build_planet(grid: (u16, u16), cell: u16, grey_values: impl Fn(u16, u16) -> u8) -> Planet {
+        let mut rlm = Realm::new(Point16::default(), Point16::default());
+        for y in 0..grid.1 {
+            for x in 0..grid.0 {
+                let site = Point16::new(x * cell + cell / 2, y * cell + cell / 2);
+                let mut rg = Region::new(Point16::new(site.x, site.y));
+                rg.grey_value = grey_values(x, y);
+                for py in y * cell..(y + 1) * cell {
+                    for px in x * cell..(x + 1) * cell {
+                        rg.pixels.push((px, py));
+                    }
+                }
+                let mut pv = Province::new(Point16::new(x, y), site);
+                pv.biome_summary.dominant = Biome::TemperateGrassland;
+                pv.regions.push(rg);
+                rlm.provinces.push(pv);
+            }
+        }
+        let mut continent = Continent::default();
+        continent.realms.push(rlm);
+
+        Planet {
+            img_size: Size16::new(grid.0 * cell, grid.1 * cell),
+            continents: HashMap::from([((0, 0), continent)]),
+            plate_boundaries: Vec::new(),
+            heightmap: None,
+            hydrology: None,
+            politics: None,
+            settlements: Vec::new(),
+            roads: Vec::new(),
+        }
+    }
+
+    //  0  1  2  3  4
+    //  5  6  7  8  9   6 is a lake, the two east columns an ocean over the seam,
+    // 10 11 12 13 14   sites are 10 pixels apart
+    fn build_lake_planet() -> Planet {
+        build_planet((5, 3), 10, |x, y| if (x, y) == (1, 1) || x >= 3 { SEA } else { LAND })
+    }
+
+    #[test]
+    fn path_goes_around_forbidden_sea() {
+        let graph = ProvinceGraph::new(&build_lake_planet(), SEA_LEVEL, 0.5);
+        assert!(!graph.nodes[6].is_land);
+
+        let path = graph.find_path(5, 7, &TerrainCost::default()).unwrap();
+        assert_eq!(path.provinces.len(), 5);
+        assert!(!path.provinces.contains(&6));
+        assert_eq!(path.cost, 40.0);
+        assert!(graph.find_path(5, 6, &TerrainCost::default()).is_none());
+    }
+
+    #[test]
+    fn path_crosses_allowed_sea() {
+        let graph = ProvinceGraph::new(&build_lake_planet(), SEA_LEVEL, 0.5);
+        let movement = TerrainCost { sea: SeaCrossing::Allowed { cost: 0.5 }, ..TerrainCost::default() };
+
+        let path = graph.find_path(5, 7, &movement).unwrap();
+        assert_eq!(path.provinces, vec![5, 6, 7]);
+        assert_eq!(path.cost, 10.0);
+    }
+
+    #[test]
+    fn path_crosses_the_seam() {
+        // the ocean wraps, from the west coast the way east is through 9
+        let graph = ProvinceGraph::new(&build_lake_planet(), SEA_LEVEL, 0.5);
+        let movement = TerrainCost { sea: SeaCrossing::Allowed { cost: 0.5 }, ..TerrainCost::default() };
+
+        assert!(graph.get_neighbours(5).any(|pv| pv == 9));
+        assert_eq!(graph.get_distance(5, 9), 10.0);
+        let path = graph.find_path(5, 8, &movement).unwrap();
+        assert_eq!(path.provinces, vec![5, 9, 8]);
+        assert_eq!(path.cost, 10.0);
+    }
+
+    #[test]
+    fn reachable_and_distance_field_skip_forbidden_sea() {
+        let graph = ProvinceGraph::new(&build_lake_planet(), SEA_LEVEL, 0.5);
+
+        let reachable = graph.find_reachable(5, 20.0, &TerrainCost::default());
+        assert_eq!(reachable, vec![(5, 0.0), (0, 10.0), (10, 10.0), (1, 20.0), (11, 20.0)]);
+
+        let field = graph.build_distance_field(&[0], &TerrainCost::default());
+        let inf = f32::INFINITY;
+        assert_eq!(field, vec![0.0, 10.0, 20.0, inf, inf, 10.0, inf, 30.0, inf, inf, 20.0, 30.0, 40.0, inf, inf]);
+        let movement = TerrainCost { sea: SeaCrossing::Allowed { cost: 0.5 }, ..TerrainCost::default() };
+        assert_eq!(graph.build_distance_field(&[0], &movement)[6], 15.0);
+    }
+
+    #[test]
+    fn land_share_threshold_decides_land() {
+        // the province holds a land and a sea region of the same size
+        let mut planet = build_planet((1, 1), 10, |_, _| LAND);
+        let mut sea = Region::new(Point16::new(15, 5));
+        sea.grey_value = SEA;
+        sea.pixels = (0..10).flat_map(|y| (10..20).map(move |x| (x, y))).collect();
+        planet.continents.get_mut(&(0, 0)).unwrap().realms[0].provinces[0].regions.push(sea);
+        planet.img_size = Size16::new(20, 10);
+
+        assert!(ProvinceGraph::new(&planet, SEA_LEVEL, 0.5).nodes[0].is_land);
+        assert!(!ProvinceGraph::new(&planet, SEA_LEVEL, 0.6).nodes[0].is_land);
+    }
+
+    // the default planet: 128x64 provinces of 12x12 pixels, rolling hills with inland seas
+    fn build_default_planet() -> Planet {
+        build_planet((128, 64), 12, |x, y| {
+            let hills = (x as f32 / 9.0).sin() * (y as f32 / 7.0).cos();
+            (140.0 + 90.0 * hills) as u8
+        })
+    }
+
+    #[test]
+    fn queries_repeat_across_runs() {
+        let planet = build_default_planet();
+        let (a, b) = (ProvinceGraph::new(&planet, SEA_LEVEL, 0.5), ProvinceGraph::new(&planet, SEA_LEVEL, 0.5));
+        let movement = TerrainCost::default();
+
+        assert_eq!(a.find_path(130, 8000, &movement), b.find_path(130, 8000, &movement));
+        assert_eq!(a.find_reachable(4000, 300.0, &movement), b.find_reachable(4000, 300.0, &movement));
+        assert_eq!(a.build_distance_field(&[1, 5000], &movement), b.build_distance_field(&[1, 5000], &movement));
+    }
+
+    // the 1 ms budget is for optimised builds, run with `cargo test --release`
+    #[test]
+    #[cfg(not(debug_assertions))]
+    fn path_queries_are_fast_on_the_default_planet() {
+        let graph = ProvinceGraph::new(&build_default_planet(), SEA_LEVEL, 0.5);
+        let movement = TerrainCost::default();
+        let queries: Vec<(u32, u32)> = (0..100).map(|i| ((i * 331) % 8192, (i * 977 + 4096) % 8192)).collect();
+
+        let start = std::time::Instant::now();
+        for &(from, to) in &queries {
+            graph.find_path(from, to, &movement);
+        }
+        let per_query = start.elapsed() / queries.len() as u32;
+        assert!(per_query.as_micros() < 1_000, "{:?} per query", per_query);
+    }
+}
@@ -3,49 +3,28 @@ use image::{imageops, DynamicImage, ImageBuffer, Luma};
 
 const LIB_NAME: &str = "tectonic-process";
 
-pub struct Color8 {
-    pub r: u8,
-    pub g: u8,
-    pub b: u8,
-}
-
-enum SurfaceDepths {
-    DeepSea = 35,   // 21
-    ShallowSea = 50,    // 42
-    Coast = 60, // 63
-
-    Plains = 84,
-    GentleHill = 105,
-    SteepHill = 126,
-
-    RuggedHill = 147,
-    LowerMountain = 168,
-    Plateaus = 189,
-
-    HighMountain = 210,
-    MountainPlateaus = 231,
-    Peaks = 252,
-}
-
-const DEEP_SEA: Color8 = Color8 { r: 28, g: 101, b: 142, };   // 1C658E  = rgb(28, 101, 142)
-const SHALLOW_SEA: Color8 = Color8 { r: 47, g: 137, b: 187 };    // 2F89BB = rgb(47, 137, 187)
-const COAST: Color8 = Color8 { r: 142, g: 182, b: 134 };    // 8EB686 = rgb(142, 182, 134)
-
-const PLAINS: Color8 = Color8 { r: 121, g: 162, b: 92 };   // 79A25C = rgb(121, 162, 92)
-const GENTLE_HILL: Color8 = Color8 { r: 138, g: 170, b: 96 };  // 8AAA60 = rgb(138, 170, 96)
-const STEEP_HILL: Color8 = Color8 { r: 167, g: 184, b: 101, };    // A7B865 = rgb(167, 184, 101)
-
-const RUGGED_HILL: Color8 = Color8 { r: 184, g: 192, b: 105 };  // B8C069 = rgb(184, 192, 105)
-const LOWER_MOUNTAIN: Color8 = Color8 { r: 239, g: 225, b: 123 };   // EFE17B = rgb(239, 225, 123)
-const PLATEAUS: Color8 = Color8 { r: 223, g: 195, b: 113 };    // DFC371 = rgb(223, 195, 113)
-
-const HIGH_MOUNTAIN: Color8 = Color8 { r: 202, g: 158, b: 100 };   // CA9E64 = rgb(202, 158, 100)
-const MOUNTAIN_PLATEAUS: Color8 = Color8 { r: 166, g: 135, b: 113 };   // A68771 = rgb(166, 135, 113)
-const PEAKS: Color8 = Color8 { r: 255, g: 255, b: 255 };   // FFFFFF
-
-use gamescript::file_read_write;
+// grey value of the sea level in world-tweak's final image, --sea-level changes it
+const DEFAULT_SEA_LEVEL: u8 = 50;
+
+use gamescript::{
+    file_read_write,
+    models::{
+        heightmap::{EdgeMode, Heightmap},
+        point::Size16,
+        render::{self, ReliefSettings},
+    },
+};
 
 fn main() {
+    let args: Vec<String> = env::args().collect();
+    let sea_level: u8 = get_arg(&args, "--sea-level").unwrap_or(DEFAULT_SEA_LEVEL);
+    let defaults = ReliefSettings::default();
+    let relief = ReliefSettings {
+        azimuth: get_arg(&args, "--azimuth").unwrap_or(defaults.azimuth),
+        altitude: get_arg(&args, "--altitude").unwrap_or(defaults.altitude),
+        strength: get_arg(&args, "--strength").unwrap_or(defaults.strength),
+        ..defaults
+    };
 
     let dir_name = file_read_write::dir_name(LIB_NAME);
     println!("{:?}", dir_name);
@@ -59,7 +38,8 @@ fn main() {
         
         let path = Path::new(&image_path);
         let loaded_img: DynamicImage = image::open(&path).unwrap();
-        let grey_img = loaded_img.to_luma8();
+        // 16 bit, so the heightmap keeps the precision of a 16 bit final image
+        let grey_img = loaded_img.to_luma16();
 
         let sharpened_img = imageops::unsharpen(&grey_img, 35.0, 5);
         sharpened_img.save("sharpened_image.png").unwrap();
@@ -69,17 +49,17 @@ fn main() {
 
         gradually_increase_light_pixels(&sharpened_img);
 
-        color_oceans_and_land(&sharpened_img);
+        color_oceans_and_land(&sharpened_img, sea_level, &relief);
 
         let blurred_img = imageops::blur(&sharpened_img, 1.2);
         blurred_img.save("blurred_image.png").unwrap();
     }
 }
 
-fn gradually_increase_light_pixels(img: &ImageBuffer<Luma<u8>, Vec<u8>>) {
-    // find very light pixels
-    const LIGHT_PIXEL: u8 = 172;
-    let mut light_pixels: HashMap<(u32, u32), u8> = HashMap::new();
+fn gradually_increase_light_pixels(img: &ImageBuffer<Luma<u16>, Vec<u16>>) {
+    // find very light pixels, 172 of 255
+    const LIGHT_PIXEL: u16 = 172 * 257;
+    let mut light_pixels: HashMap<(u32, u32), u16> = HashMap::new();
     for (x, y, pixel) in img.enumerate_pixels() {
         let px_value = pixel.0[0];
         // println!("px_value: {}", px_value);
@@ -90,7 +70,7 @@ fn gradually_increase_light_pixels(img: &ImageBuffer<Luma<u8>, Vec<u8>>) {
 
     println!("{}", light_pixels.len());
 
-    let mut img_buf: ImageBuffer<Luma<u8>, Vec<u8>> =
+    let mut img_buf: ImageBuffer<Luma<u16>, Vec<u16>> =
         ImageBuffer::new(img.dimensions().0 as u32, img.dimensions().1 as u32);
     for x in 0..img.dimensions().0 {
         for y in 0..img.dimensions().1 {
@@ -109,53 +89,31 @@ fn gradually_increase_light_pixels(img: &ImageBuffer<Luma<u8>, Vec<u8>>) {
     img_buf.save("light_pixels.png");
 }
 
-fn color_oceans_and_land(img: &ImageBuffer<Luma<u8>, Vec<u8>>) {
+// hypsometric tint shaded by the relief, so the map shows the terrain shape and not only its height
+fn color_oceans_and_land(img: &ImageBuffer<Luma<u16>, Vec<u16>>, sea_level: u8, relief: &ReliefSettings) {
     let width = img.dimensions().0;
     let height = img.dimensions().1;
-    let mut img_buf: ImageBuffer<image::Rgb<u8>, Vec<u8>> = ImageBuffer::new(width, height);
-
-    for x in 0..width {
-        for y in 0..height {
-            let img_pixel = img.get_pixel(x, y);
-            let grey_value = img_pixel.0[0];
-
-            let mut color: Color8 = get_pixel_color_by_depth(grey_value);
+    let mut heightmap = Heightmap::new(Size16::new(width as u16, height as u16));
+    for (x, y, pixel) in img.enumerate_pixels() {
+        heightmap.set(x as u16, y as u16, pixel.0[0] as f32 / u16::MAX as f32);
+    }
 
-            let pixel = img_buf.get_pixel_mut(x, y);
-            *pixel = image::Rgb([color.r, color.g, color.b]);
-        }
+    let stops = render::get_hypsometric_ramp(sea_level as f32 / u8::MAX as f32);
+    let lights = relief.get_lights();
+    let mut img_buf: ImageBuffer<image::Rgb<u8>, Vec<u8>> = ImageBuffer::new(width, height);
+    for (x, y, pixel) in img_buf.enumerate_pixels_mut() {
+        let (x, y) = (x as u16, y as u16);
+        let tint = render::sample_ramp(&stops, heightmap.get(x, y));
+        let shade = relief.get_shade(heightmap.get_normal(x, y, [relief.height_scale; 2], EdgeMode::WrapHorizontal), &lights);
+        let light = 1.0 - relief.strength + relief.strength * shade;
+        *pixel = image::Rgb(tint.map(|channel| (channel as f32 * light).round() as u8));
     }
 
     img_buf.save("earth_pixels.png");
 }
 
-fn get_pixel_color_by_depth(grey_value: u8) -> Color8 {
-
-    if grey_value < SurfaceDepths::DeepSea as u8 {
-        return DEEP_SEA;
-    } else if grey_value < SurfaceDepths::ShallowSea as u8 {
-        return SHALLOW_SEA;
-    } else if grey_value < SurfaceDepths::Coast as u8 {
-        return COAST;
-
-    } else if grey_value < SurfaceDepths::Plains as u8 {
-        return PLAINS;
-    } else if grey_value < SurfaceDepths::GentleHill as u8 {
-        return GENTLE_HILL;
-    } else if grey_value < SurfaceDepths::SteepHill as u8 {
-        return STEEP_HILL;
-
-    } else if grey_value < SurfaceDepths::RuggedHill as u8 {
-        return RUGGED_HILL;
-    } else if grey_value < SurfaceDepths::LowerMountain as u8 {
-        return LOWER_MOUNTAIN;
-    } else if grey_value < SurfaceDepths::Plateaus as u8 {
-        return PLATEAUS;
-
-    } else if grey_value < SurfaceDepths::HighMountain as u8 {
-        return HIGH_MOUNTAIN;
-    } else if grey_value < SurfaceDepths::MountainPlateaus as u8 {
-        return MOUNTAIN_PLATEAUS;
-    }
-    return PEAKS;
+// value following the flag, None when the flag is missing or its value doesn't parse
+fn get_arg<T: std::str::FromStr>(args: &[String], name: &str) -> Option<T> {
+    let index = args.iter().position(|arg| arg == name)?;
+    args.get(index + 1)?.parse().ok()
 }
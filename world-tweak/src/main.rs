@@ -2,7 +2,8 @@ mod equalize_realms;
 mod seamless;
 
 use gamescript::{
-    bin_read_write, file_read_write, json_read_write, models::continent::PlanetSettings,
+    bin_read_write, file_read_write, json_read_write,
+    models::continent::{Planet, PlanetSettings},
 };
 use image::Rgb;
 pub const LIB_NAME: &str = "world-tweak";
@@ -11,7 +12,10 @@ fn main() {
     let dir_name: Option<String> = file_read_write::dir_name(LIB_NAME);
     let dist_folder: &str = &format!("{}{}", dir_name.unwrap(), "__dist");
     let path = &format!("{}\\{}", dist_folder, "planet.bin");
-    let planet = bin_read_write::deserialize_bin(path);
+    let planet: Planet = match bin_read_write::try_deserialize_versioned_bin(path, Planet::VERSION) {
+        Ok(planet) => planet,
+        Err(err) => panic!("{}, run world build-and-draw to build the planet again", err),
+    };
     let path: &String = &format!("{}\\{}", dist_folder, "planet_settings.json");
     let planet_settings: PlanetSettings = json_read_write::deserialize_json(path);
 
@@ -36,8 +40,9 @@ fn main() {
                             if exists {
                                 break;
                             }
+                            let value = planet.get_pixel_grey_value(px.0, px.1, rg.grey_value);
                             let pixel = img_buf.get_pixel_mut(px.0 as u32, px.1 as u32);
-                            *pixel = Rgb([rg.grey_value, rg.grey_value, rg.grey_value]);
+                            *pixel = Rgb([value, value, value]);
                         }
                     }
                 }
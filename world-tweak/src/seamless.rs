@@ -33,13 +33,14 @@ pub fn make(planet: &Planet, planet_settings: &PlanetSettings) {
             for rlm in &continent.realms {
                 for pv in &rlm.provinces {
                     for rg in &pv.regions {
-                        let color = if is_movable_left == true {
-                            Rgb([204, 255, 102])
-                        } else {
-                            Rgb([rg.grey_value, rg.grey_value, rg.grey_value])
-                        };
-
                         for px in &rg.pixels {
+                            let color = if is_movable_left == true {
+                                Rgb([204, 255, 102])
+                            } else {
+                                let value = planet.get_pixel_grey_value(px.0, px.1, rg.grey_value);
+                                Rgb([value, value, value])
+                            };
+
                             let pixel = img_buf.get_pixel_mut(px.0 as u32, px.1 as u32);
                             *pixel = color;
                         }
@@ -82,13 +83,14 @@ pub fn make(planet: &Planet, planet_settings: &PlanetSettings) {
             for rlm in &continent.realms {
                 for pv in &rlm.provinces {
                     for rg in &pv.regions {
-                        let color = if is_replaceable_right == true {
-                            Rgb([204, 255, 102])
-                        } else {
-                            Rgb([rg.grey_value, rg.grey_value, rg.grey_value])
-                        };
-
                         for px in &rg.pixels {
+                            let color = if is_replaceable_right == true {
+                                Rgb([204, 255, 102])
+                            } else {
+                                let value = planet.get_pixel_grey_value(px.0, px.1, rg.grey_value);
+                                Rgb([value, value, value])
+                            };
+
                             let pixel = img_buf.get_pixel_mut(
                                 px.0 as u32 + pixel_distance.0,
                                 px.1 as u32 + pixel_distance.1,
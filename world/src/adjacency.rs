@@ -0,0 +1,82 @@
+use gamescript::models::{
+    continent::{Planet, Realm, Region},
+    point::Size16,
+};
+use std::collections::VecDeque;
+
+pub use gamescript::adjacency::{
+    build_adjacency, build_owner_raster, build_regions_owner_raster, get_sorted_continent_keys, get_sorted_regions,
+    NO_OWNER,
+};
+
+pub fn build_realms_owner_raster(img_size: &Size16, realms: &[Realm]) -> Vec<u32> {
+    let mut owners: Vec<u32> = vec![NO_OWNER; img_size.width as usize * img_size.height as usize];
+
+    for (i, rlm) in realms.iter().enumerate() {
+        for pv in &rlm.provinces {
+            for rg in &pv.regions {
+                for px in &rg.pixels {
+                    owners[px.1 as usize * img_size.width as usize + px.0 as usize] = i as u32;
+                }
+            }
+        }
+    }
+
+    owners
+}
+
+// breadth first hops from the nearest source region, u32::MAX when none is reachable
+pub fn get_hop_distances(is_source: &[bool], adjacency: &[Vec<usize>]) -> Vec<u32> {
+    let mut distances: Vec<u32> = vec![u32::MAX; is_source.len()];
+    let mut queue: VecDeque<usize> = VecDeque::new();
+    for (i, &source) in is_source.iter().enumerate() {
+        if source {
+            distances[i] = 0;
+            queue.push_back(i);
+        }
+    }
+    while let Some(region) = queue.pop_front() {
+        for &next in &adjacency[region] {
+            if distances[next] == u32::MAX {
+                distances[next] = distances[region] + 1;
+                queue.push_back(next);
+            }
+        }
+    }
+    distances
+}
+
+// Where every region stands to the water around it, in the numbering of `get_sorted_regions`.
+pub struct RegionWater {
+    pub elevations: Vec<f32>,
+    pub is_sea: Vec<bool>,
+    // rivers and lakes
+    pub is_fresh_water: Vec<bool>,
+    // hops to the nearest region of each kind
+    pub sea_distances: Vec<u32>,
+    pub land_distances: Vec<u32>,
+    pub fresh_water_distances: Vec<u32>,
+}
+
+pub fn build_region_water(planet: &Planet, regions: &[&Region], adjacency: &[Vec<usize>], sea_level: f32) -> RegionWater {
+    let elevations: Vec<f32> = regions.iter().map(|rg| planet.get_region_elevation(rg)).collect();
+    let is_sea: Vec<bool> = elevations.iter().map(|&elevation| elevation < sea_level).collect();
+    let mut is_fresh_water: Vec<bool> = vec![false; regions.len()];
+    if let Some(hydrology) = &planet.hydrology {
+        let river_regions = hydrology.rivers.iter().flat_map(|river| &river.regions);
+        let lake_regions = hydrology.lakes.iter().flat_map(|lake| &lake.regions);
+        for &region in river_regions.chain(lake_regions) {
+            is_fresh_water[region as usize] = true;
+        }
+    }
+    let is_land: Vec<bool> = is_sea.iter().map(|sea| !sea).collect();
+
+    RegionWater {
+        sea_distances: get_hop_distances(&is_sea, adjacency),
+        land_distances: get_hop_distances(&is_land, adjacency),
+        fresh_water_distances: get_hop_distances(&is_fresh_water, adjacency),
+        elevations,
+        is_sea,
+        is_fresh_water,
+    }
+}
@@ -0,0 +1,182 @@
+use gamescript::models::{
+    climate::{Biome, BiomeSummary, ClimateSettings},
+    continent::{Planet, PlanetSettings, Region},
+    point::Size16,
+};
+use std::f32::consts::FRAC_PI_2;
+
+use crate::adjacency;
+
+// relative wetness of the wind bands, by latitude in degrees: rainy equator, dry horse
+// latitudes around 30, wet westerlies around 60 and dry poles
+const PRECIPITATION_BANDS: [(f32, f32); 7] = [
+    (0.0, 1.0),
+    (15.0, 0.7),
+    (30.0, 0.15),
+    (45.0, 0.55),
+    (60.0, 0.6),
+    (75.0, 0.25),
+    (90.0, 0.1),
+];
+
+const TERRAIN_SMOOTHING: f32 = 0.05;
+
+// Gives every region a temperature, a precipitation and a biome, then sums the biomes
+// up per province and realm. Temperature falls with latitude and height, precipitation
+// comes from winds carrying moisture off the sea along their latitude band.
+pub fn apply_climate(planet: &mut Planet, planet_settings: &PlanetSettings) {
+    let settings = &planet_settings.climate_settings;
+    let sea_level = planet_settings.tectonic_settings.sea_level;
+    let elevations = planet.get_pixel_elevations();
+    let precipitation = build_precipitation_raster(&planet.img_size, &elevations, sea_level, settings);
+    let width = planet.img_size.width as usize;
+    let height = planet.img_size.height as f32;
+
+    let keys = adjacency::get_sorted_continent_keys(&planet.continents);
+    let elevation_by_region: Vec<f32> = adjacency::get_sorted_regions(&planet.continents, &keys)
+        .iter()
+        .map(|rg| planet.get_region_elevation(rg))
+        .collect();
+
+    // lakes come from the hydrology pass, which numbers regions the same way
+    let mut is_lake: Vec<bool> = vec![false; elevation_by_region.len()];
+    if let Some(hydrology) = &planet.hydrology {
+        for lake in &hydrology.lakes {
+            for &region in &lake.regions {
+                is_lake[region as usize] = true;
+            }
+        }
+    }
+
+    let mut i: usize = 0;
+    for key in &keys {
+        let continent = planet.continents.get_mut(key).unwrap();
+        for rlm in &mut continent.realms {
+            for pv in &mut rlm.provinces {
+                for rg in &mut pv.regions {
+                    let elevation = elevation_by_region[i];
+                    let latitude = ((rg.site_point.y as f32 + 0.5) / height - 0.5).abs() * 2.0;
+                    let cooling = 1.0 - (latitude * FRAC_PI_2).cos();
+                    let height_above_sea = ((elevation - sea_level) / (1.0 - sea_level)).max(0.0);
+                    rg.temperature = settings.equator_temperature
+                        + (settings.pole_temperature - settings.equator_temperature) * cooling
+                        - settings.lapse_rate * height_above_sea;
+
+                    rg.precipitation = if rg.pixels.is_empty() {
+                        let site = rg.site_point.y as usize * width + rg.site_point.x as usize;
+                        precipitation.get(site).copied().unwrap_or(0.0)
+                    } else {
+                        let sum: f32 = rg.pixels.iter()
+                            .map(|px| precipitation[px.1 as usize * width + px.0 as usize])
+                            .sum();
+                        sum / rg.pixels.len() as f32
+                    };
+
+                    rg.biome = if elevation < sea_level {
+                        Biome::Ocean
+                    } else if is_lake[i] {
+                        Biome::Lake
+                    } else {
+                        Biome::classify(rg.temperature, rg.precipitation)
+                    };
+                    i += 1;
+                }
+
+                pv.biome_summary = build_biome_summary(pv.regions.iter());
+            }
+
+            rlm.biome_summary = build_biome_summary(rlm.provinces.iter().flat_map(|pv| &pv.regions));
+        }
+    }
+}
+
+// Walks every row twice along its wind so the moisture has settled when the second lap is
+// recorded, the map wraps around horizontally. Winds soak up moisture over the sea and
+// lose it over land, faster while climbing, so the lee side of a range stays dry.
+fn build_precipitation_raster(
+    img_size: &Size16,
+    elevations: &[f32],
+    sea_level: f32,
+    settings: &ClimateSettings,
+) -> Vec<f32> {
+    let width = img_size.width as usize;
+    let height = img_size.height as usize;
+    let mut precipitation: Vec<f32> = vec![0.0; width * height];
+
+    for y in 0..height {
+        let latitude = ((y as f32 + 0.5) / height as f32 - 0.5).abs() * 180.0;
+        let band = get_band_precipitation(latitude);
+        // trade winds and polar easterlies blow west, the westerlies east
+        let eastwards = (30.0..60.0).contains(&latitude);
+        let row = &elevations[y * width..(y + 1) * width];
+
+        let mut moisture: f32 = 1.0;
+        // the wind follows a smoothed terrain, pixel detail shouldn't count as climbing
+        let mut terrain = row[if eastwards { 0 } else { width - 1 }];
+        for step in 0..width * 2 {
+            let x = if eastwards { step % width } else { width - 1 - step % width };
+            let elevation = row[x];
+            let previous = terrain;
+            terrain += (elevation - terrain) * TERRAIN_SMOOTHING;
+            let rise = (terrain - previous).max(0.0);
+
+            let wetness = if elevation < sea_level {
+                moisture = (moisture + settings.evaporation).min(1.0);
+                moisture
+            } else {
+                let fraction = (settings.rainfall_rate + rise * settings.orographic_factor).min(1.0);
+                let wetness = moisture * (fraction / settings.rainfall_rate.max(f32::EPSILON)).sqrt();
+                moisture -= moisture * fraction;
+                wetness
+            };
+
+            if step >= width {
+                precipitation[y * width + x] = settings.max_precipitation * band * wetness.min(1.0);
+            }
+        }
+    }
+
+    precipitation
+}
+
+fn get_band_precipitation(latitude: f32) -> f32 {
+    for pair in PRECIPITATION_BANDS.windows(2) {
+        let ((from, a), (to, b)) = (pair[0], pair[1]);
+        if latitude <= to {
+            let t = ((latitude - from) / (to - from)).clamp(0.0, 1.0);
+            return a + (b - a) * t;
+        }
+    }
+    PRECIPITATION_BANDS[PRECIPITATION_BANDS.len() - 1].1
+}
+
+fn build_biome_summary<'a>(regions: impl Iterator<Item = &'a Region>) -> BiomeSummary {
+    let mut pixels: Vec<u32> = vec![0; Biome::ALL.len()];
+    let mut temperature_sum: f64 = 0.0;
+    let mut precipitation_sum: f64 = 0.0;
+    let mut pixels_len: u32 = 0;
+
+    for rg in regions {
+        let len = rg.pixels.len() as u32;
+        pixels[rg.biome as usize] += len;
+        temperature_sum += rg.temperature as f64 * len as f64;
+        precipitation_sum += rg.precipitation as f64 * len as f64;
+        pixels_len += len;
+    }
+
+    let mut biomes: Vec<(Biome, u32)> = Biome::ALL
+        .iter()
+        .map(|&biome| (biome, pixels[biome as usize]))
+        .filter(|(_, len)| *len > 0)
+        .collect();
+    // stable sort keeps the enum order for ties
+    biomes.sort_by_key(|(_, len)| std::cmp::Reverse(*len));
+
+    let len = pixels_len.max(1) as f64;
+    BiomeSummary {
+        dominant: biomes.first().map_or(Biome::Ocean, |(biome, _)| *biome),
+        pixels: biomes,
+        average_temperature: (temperature_sum / len) as f32,
+        average_precipitation: (precipitation_sum / len) as f32,
+    }
+}
@@ -0,0 +1,88 @@
+// Greedy graph colouring, so neighbouring cells of a map never share a colour.
+
+// Well separated colours for categorical maps, the ColorBrewer Set3 scheme.
+pub const CATEGORICAL_COLORS: [[u8; 3]; 12] = [
+    [141, 211, 199],
+    [255, 255, 179],
+    [190, 186, 218],
+    [251, 128, 114],
+    [128, 177, 211],
+    [253, 180, 98],
+    [179, 222, 105],
+    [252, 205, 229],
+    [217, 217, 217],
+    [188, 128, 189],
+    [204, 235, 197],
+    [255, 237, 111],
+];
+
+// Colour index of every node. Nodes are visited from the most connected down, ties by index,
+// and each takes the least used colour none of its coloured neighbours has, so the colours
+// spread evenly over the map. More colours than `min_colors` are only used when a node's
+// neighbours already take all of them. The result only depends on the graph.
+pub fn color_graph(adjacency: &[Vec<usize>], min_colors: usize) -> Vec<usize> {
+    let mut order: Vec<usize> = (0..adjacency.len()).collect();
+    order.sort_by(|&a, &b| adjacency[b].len().cmp(&adjacency[a].len()).then(a.cmp(&b)));
+
+    let mut colors: Vec<Option<usize>> = vec![None; adjacency.len()];
+    let mut counts: Vec<usize> = vec![0; min_colors.max(1)];
+    for node in order {
+        let taken: Vec<usize> = adjacency[node].iter().filter_map(|&n| colors[n]).collect();
+        let free = (0..counts.len())
+            .filter(|color| !taken.contains(color))
+            .min_by_key(|&color| (counts[color], color));
+        let color = match free {
+            Some(color) => color,
+            None => {
+                counts.push(0);
+                counts.len() - 1
+            }
+        };
+        counts[color] += 1;
+        colors[node] = Some(color);
+    }
+
+    colors.into_iter().map(|color| color.unwrap_or(0)).collect()
+}
+
+// RGB of a colour index, past the categorical set the hue turns by the golden angle
+pub fn get_categorical_color(index: usize) -> [u8; 3] {
+    if index < CATEGORICAL_COLORS.len() {
+        return CATEGORICAL_COLORS[index];
+    }
+    let hue = ((index - CATEGORICAL_COLORS.len()) as f32 * 137.507_77 + 20.0) % 360.0;
+    let (saturation, value) = (0.45, 0.9);
+    let chroma = value * saturation;
+    let x = chroma * (1.0 - ((hue / 60.0) % 2.0 - 1.0).abs());
+    let (r, g, b) = match (hue / 60.0) as u32 {
+        0 => (chroma, x, 0.0),
+        1 => (x, chroma, 0.0),
+        2 => (0.0, chroma, x),
+        3 => (0.0, x, chroma),
+        4 => (x, 0.0, chroma),
+        _ => (chroma, 0.0, x),
+    };
+    let m = value - chroma;
+    [r, g, b].map(|channel| ((channel + m) * 255.0).round() as u8)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn neighbours_never_share_a_color() {
+        // a wheel, the hub touches every node of a five node ring
+        let mut adjacency: Vec<Vec<usize>> = vec![(1..6).collect()];
+        for i in 1..6 {
+            adjacency.push(vec![0, (i % 5) + 1, ((i + 3) % 5) + 1]);
+        }
+        let colors = color_graph(&adjacency, 3);
+        for (node, neighbours) in adjacency.iter().enumerate() {
+            assert!(neighbours.iter().all(|&n| colors[n] != colors[node]));
+        }
+        // an odd ring around a hub needs four colours
+        assert_eq!(colors.iter().max(), Some(&3));
+        assert_eq!(colors, color_graph(&adjacency, 3));
+    }
+}
@@ -1,13 +1,19 @@
 use gamescript::models::{
     continent::{Continent, Province, Realm, Region, PlanetSettings},
-    point::{try_map_points_min_max_points_by_points, calculate_pixel_pos, calculate_distance, normalize_u8, denormalize_u8, Point16, Size16},
+    point::{try_map_points_min_max_points_by_points, calculate_distance, denormalize_u8, Point16, Size16},
+    tectonics::{PlateSettings, PlateType},
 };
-use image::GrayImage;
-use rand::Rng;
+use rand::{rngs::StdRng, Rng, SeedableRng};
 use std::collections::HashMap;
 use voronoice::Point;
 use world::image_gradient;
 
+use crate::progress::{self, Verbosity};
+
+pub const PROVINCE_SITES_SALT: u64 = 0x5eed_0008;
+pub const REALM_SITES_SALT: u64 = 0x5eed_0009;
+pub const CONTINENT_SITES_SALT: u64 = 0x5eed_000a;
+
 pub fn build_regions_and_assign_sites(sites: Vec<Point>) -> Vec<Region> {
     let mut regions = Vec::with_capacity(sites.len());
 
@@ -22,14 +28,14 @@ pub fn build_regions_and_assign_sites(sites: Vec<Point>) -> Vec<Region> {
 }
 
 pub fn build_provinces_and_generate_sites(planet_settings: &PlanetSettings) -> Vec<Province> {
-
+    let mut rng = StdRng::seed_from_u64(planet_settings.seed ^ PROVINCE_SITES_SALT);
     let mut provinces: Vec<Province> =
         Vec::with_capacity((planet_settings.province_grid_size.width * planet_settings.province_grid_size.height) as usize);
 
     for x in 0..planet_settings.province_grid_size.width {
         for y in 0..planet_settings.province_grid_size.height {
-            let random_x = rand::thread_rng().gen_range(0..planet_settings.province_cell_size.width);
-            let random_y = rand::thread_rng().gen_range(0..planet_settings.province_cell_size.height);
+            let random_x = rng.gen_range(0..planet_settings.province_cell_size.width);
+            let random_y = rng.gen_range(0..planet_settings.province_cell_size.height);
             let site_point = Point16 {
                 x: ((x * planet_settings.province_cell_size.width) + random_x),
                 y: ((y * planet_settings.province_cell_size.height) + random_y),
@@ -43,12 +49,13 @@ pub fn build_provinces_and_generate_sites(planet_settings: &PlanetSettings) -> V
 }
 
 pub fn build_realms_and_generate_sites(planet_setting: &PlanetSettings) -> Vec<Realm> {
+    let mut rng = StdRng::seed_from_u64(planet_setting.seed ^ REALM_SITES_SALT);
     let mut realms: Vec<Realm> = Vec::with_capacity((planet_setting.realm_grid_size.width * planet_setting.realm_cell_size.height) as usize);
 
     for x in 0..planet_setting.realm_grid_size.width {
         for y in 0..planet_setting.realm_grid_size.height {
-            let random_x = rand::thread_rng().gen_range(0..planet_setting.realm_cell_size.width);
-            let random_y = rand::thread_rng().gen_range(0..planet_setting.realm_cell_size.height);
+            let random_x = rng.gen_range(0..planet_setting.realm_cell_size.width);
+            let random_y = rng.gen_range(0..planet_setting.realm_cell_size.height);
             let site_point = Point16 {
                 x: ((x * planet_setting.realm_cell_size.width) + random_x),
                 y: ((y * planet_setting.realm_cell_size.height) + random_y),
@@ -62,12 +69,13 @@ pub fn build_realms_and_generate_sites(planet_setting: &PlanetSettings) -> Vec<R
 }
 
 pub fn build_continents_with_site(planet_settings: &PlanetSettings) -> HashMap<(u16, u16), Continent> {
+    let mut rng = StdRng::seed_from_u64(planet_settings.seed ^ CONTINENT_SITES_SALT);
     let mut continents: HashMap<(u16, u16), Continent> = HashMap::new();
 
     for x in 0..planet_settings.continent_grid_size.width {
         for y in 0..planet_settings.continent_grid_size.height {
-            let random_x = rand::thread_rng().gen_range(0..planet_settings.continent_cell_size.width);
-            let random_y = rand::thread_rng().gen_range(0..planet_settings.continent_cell_size.height);
+            let random_x = rng.gen_range(0..planet_settings.continent_cell_size.width);
+            let random_y = rng.gen_range(0..planet_settings.continent_cell_size.height);
             let site = Point16 {
                 x: (x * planet_settings.continent_cell_size.width) + random_x,
                 y: (y * planet_settings.continent_cell_size.height) + random_y,
@@ -76,8 +84,9 @@ pub fn build_continents_with_site(planet_settings: &PlanetSettings) -> HashMap<(
             let continent_point = Continent::new(
                 Point16 { x, y },
                 site,
-                image_gradient::get_random_degrees_index(),
-                get_random_tectonic_elevation(),
+                image_gradient::get_random_degrees_index(&mut rng),
+                // elevation depends on the plate type, which is known once realms are assigned
+                0.0,
             );
 
             continents.insert((x, y), continent_point);
@@ -157,6 +166,10 @@ pub fn assign_regions_to_provinces(
                 bottom_left: region.bottom_left,
                 top_right: region.top_right,
                 grey_value: region.grey_value,
+                temperature: region.temperature,
+                precipitation: region.precipitation,
+                biome: region.biome,
+                resources: region.resources,
                 pixels: region.pixels,
             };
 
@@ -238,11 +251,14 @@ pub fn assign_provinces_to_realms(
         let realm_index: usize = realms_hmap[&(nearest_point.x, nearest_point.y)].4 as usize;
         if let Some(realm) = realms.get_mut(realm_index) {
             let pv = Province {
+                name: province.name,
                 grid_coord: province.grid_coord,
                 site_point: province.site_point,
                 top_right: province.top_right,
                 bottom_left: province.bottom_left,
                 average_grey_value: province.average_grey_value,
+                biome_summary: province.biome_summary,
+                resources: province.resources,
                 regions: province.regions,
             };
 
@@ -309,11 +325,14 @@ pub fn assign_realms_to_continents_and_calculate_region_color(
             .get_mut(&(nearest_point.x, nearest_point.y))
             .map(|continent| {
                 let rlm = Realm {
+                    name: realm.name,
                     grid_coord: realm.grid_coord,
                     site_point: realm.site_point,
                     top_right: realm.top_right,
                     bottom_left: realm.bottom_left,
                     average_grey_value: realm.average_grey_value,
+                    biome_summary: realm.biome_summary,
+                    resources: realm.resources,
                     provinces: realm.provinces,
                 };
 
@@ -338,16 +357,16 @@ pub fn merge_continents(
     
     let mut new_continents: HashMap<(u16, u16), Continent> = HashMap::new();
 
-    println!("{:?}", continents.len());
+    progress::log(Verbosity::Debug, format_args!("{:?}", continents.len()));
 
     // create top left
     let inserted_coord = (0, 0);
     let mut continent = continents.remove(&inserted_coord).unwrap();
-    println!("into: {:?} <- INSERT {:?}", inserted_coord, inserted_coord);
+    progress::log(Verbosity::Trace, format_args!("into: {:?} <- INSERT {:?}", inserted_coord, inserted_coord));
     new_continents.insert(inserted_coord, continent);
 
-    println!("new_continents.len(): {:?}", new_continents.len());
-    println!("- FINISHED create top left -> {:?}", continents.len());
+    progress::log(Verbosity::Debug, format_args!("new_continents.len(): {:?}", new_continents.len()));
+    progress::log(Verbosity::Debug, format_args!("- FINISHED create top left -> {:?}", continents.len()));
 
 
 
@@ -364,7 +383,7 @@ pub fn merge_continents(
                 continent.realms.push(realm);
             }
     
-            println!("into: {:?} <- INSERT {:?} + realms", inserted_coord, to_remove);
+            progress::log(Verbosity::Trace, format_args!("into: {:?} <- INSERT {:?} + realms", inserted_coord, to_remove));
             new_continents.insert(inserted_coord, continent);
         } else if y > 2 {
 
@@ -372,12 +391,12 @@ pub fn merge_continents(
             let to_remove = &(x, y);
             let continent = continents.remove(to_remove).unwrap();
 
-            println!("into: {:?} <- INSERT {:?}", inserted_coord, to_remove);
+            progress::log(Verbosity::Trace, format_args!("into: {:?} <- INSERT {:?}", inserted_coord, to_remove));
             new_continents.insert(inserted_coord, continent);
         }
     }
-    println!("- finished first column {:?}", continents.len());
-    println!("new_continents.len(): {:?}", new_continents.len());
+    progress::log(Verbosity::Debug, format_args!("- finished first column {:?}", continents.len()));
+    progress::log(Verbosity::Debug, format_args!("new_continents.len(): {:?}", new_continents.len()));
 
     // first row
     for x in 1..planet_settings.continent_grid_size.width {
@@ -392,7 +411,7 @@ pub fn merge_continents(
                 continent.realms.push(realm);
             }
     
-            println!("into: {:?} <- INSERT {:?} + realms", inserted_coord, to_remove);
+            progress::log(Verbosity::Trace, format_args!("into: {:?} <- INSERT {:?} + realms", inserted_coord, to_remove));
             new_continents.insert(inserted_coord, continent);
         } else if x > 2 {
 
@@ -400,7 +419,7 @@ pub fn merge_continents(
             let to_remove = &(x, y);
             let continent = continents.remove(to_remove).unwrap();
 
-            println!("into: {:?} <- INSERT {:?}", inserted_coord, to_remove);
+            progress::log(Verbosity::Trace, format_args!("into: {:?} <- INSERT {:?}", inserted_coord, to_remove));
             new_continents.insert(inserted_coord, continent);
         }
     }
@@ -422,13 +441,13 @@ pub fn merge_continents(
         continent.realms.push(realm);
     }
 
-    println!("into: {:?} <- INSERT {:?} + realms", inserted_coord, (2, 1));
-    println!("into: {:?} <- INSERT {:?} + realms", inserted_coord, (1, 2));
-    println!("into: {:?} <- INSERT {:?} + realms", inserted_coord, (2, 2));
+    progress::log(Verbosity::Trace, format_args!("into: {:?} <- INSERT {:?} + realms", inserted_coord, (2, 1)));
+    progress::log(Verbosity::Trace, format_args!("into: {:?} <- INSERT {:?} + realms", inserted_coord, (1, 2)));
+    progress::log(Verbosity::Trace, format_args!("into: {:?} <- INSERT {:?} + realms", inserted_coord, (2, 2)));
     new_continents.insert(inserted_coord, continent);
 
-    println!("- finished create the 4 cubed -> {:?}", continents.len());
-    println!("new_continents.len(): {:?}", new_continents.len());
+    progress::log(Verbosity::Debug, format_args!("- finished create the 4 cubed -> {:?}", continents.len()));
+    progress::log(Verbosity::Debug, format_args!("new_continents.len(): {:?}", new_continents.len()));
 
     // create the 2 2 vertical
     for y in 3..planet_settings.continent_grid_size.height {
@@ -443,12 +462,12 @@ pub fn merge_continents(
             continent.realms.push(realm);
         }
 
-        println!("into: {:?} <- INSERT {:?} + realms of {:?}", inserted_coord, moved_coord, to_remove);
+        progress::log(Verbosity::Trace, format_args!("into: {:?} <- INSERT {:?} + realms of {:?}", inserted_coord, moved_coord, to_remove));
         new_continents.insert(inserted_coord, continent);
     }
 
-    println!("- create the 2 2 vertical -> {:?}", continents.len());
-    println!("new_continents.len(): {:?}", new_continents.len());
+    progress::log(Verbosity::Debug, format_args!("- create the 2 2 vertical -> {:?}", continents.len()));
+    progress::log(Verbosity::Debug, format_args!("new_continents.len(): {:?}", new_continents.len()));
 
     // create the 2 2 horizontal
     for x in 3..planet_settings.continent_grid_size.width {
@@ -463,12 +482,12 @@ pub fn merge_continents(
             continent.realms.push(realm);
         }
 
-        println!("into: {:?} <- INSERT {:?} + realms of {:?}", inserted_coord, moved_coord, to_remove);
+        progress::log(Verbosity::Trace, format_args!("into: {:?} <- INSERT {:?} + realms of {:?}", inserted_coord, moved_coord, to_remove));
         new_continents.insert(inserted_coord, continent);
     }
 
-    println!("- FINISHED create the 2 2 horizontal -> {:?}", continents.len());
-    println!("new_continents.len(): {:?}", new_continents.len());
+    progress::log(Verbosity::Debug, format_args!("- FINISHED create the 2 2 horizontal -> {:?}", continents.len()));
+    progress::log(Verbosity::Debug, format_args!("new_continents.len(): {:?}", new_continents.len()));
 
 
     // move the rest one index less
@@ -479,13 +498,13 @@ pub fn merge_continents(
             let to_remove = &(x, y);
             let continent = continents.remove(to_remove).unwrap();
 
-            println!("into: {:?} <- INSERT {:?}", inserted_coord, to_remove);
+            progress::log(Verbosity::Trace, format_args!("into: {:?} <- INSERT {:?}", inserted_coord, to_remove));
             new_continents.insert(inserted_coord, continent);
         }
     }
 
-    println!("- FINISHED move the rest one index less -> {:?}", continents.len());
-    println!("new_continents.len(): {:?}", new_continents.len());
+    progress::log(Verbosity::Debug, format_args!("- FINISHED move the rest one index less -> {:?}", continents.len()));
+    progress::log(Verbosity::Debug, format_args!("new_continents.len(): {:?}", new_continents.len()));
 
     new_continents
 }
@@ -494,73 +513,70 @@ pub fn assign_continent_gradient_to_pixels(
     continents: &mut HashMap<(u16, u16), Continent>,
     planet_settings: &PlanetSettings
 ) {
-    // load gradient images so we can calculate the pixel value of the region
-    let mut gradient_images: HashMap<u8, GrayImage> = HashMap::new();
-    for continent in continents.values() {
-        let exists = gradient_images.contains_key(&continent.plate_movement_direction);
-        if exists == false {
-            let degrees = image_gradient::get_degrees_by_index(continent.plate_movement_direction);
-            let gradient_image: GrayImage = image_gradient::load_gradient(degrees);
-            gradient_images.insert(continent.plate_movement_direction, gradient_image);
-        }
-    }
+    let plate_settings = &planet_settings.plate_settings;
 
     // iterate over regions and assign continent gradient color to pixels
-    for x in 0..planet_settings.continent_grid_size.width {
-        for y in 0..planet_settings.continent_grid_size.height {
-            continents.get_mut(&(x, y)).map(|continent| {
-                let square_size = Size16::new(
-                    continent.top_right.x - continent.bottom_left.x,
-                    continent.top_right.y - continent.bottom_left.y,
-                );
-
-                if let Some(gradient_texture) =
-                    gradient_images.get(&continent.plate_movement_direction)
-                {
-                    let per_pixel_size = (
-                        (gradient_texture.width() as f64 / square_size.width as f64) as f64,
-                        (gradient_texture.height() as f64 / square_size.height as f64) as f64,
+    for continent in continents.values_mut() {
+        let square_size = Size16::new(
+            continent.top_right.x.saturating_sub(continent.bottom_left.x).max(1),
+            continent.top_right.y.saturating_sub(continent.bottom_left.y).max(1),
+        );
+        let degrees = image_gradient::get_degrees_by_index(continent.plate_movement_direction);
+        let seed = ((continent.grid_coord.x as u32) << 16) | continent.grid_coord.y as u32;
+
+        for rlm in &mut continent.realms {
+            for pv in &mut rlm.provinces {
+                for rg in &mut pv.regions {
+                    let gradient_pos = Point16::substract(&rg.site_point, &continent.bottom_left);
+                    let gradient_value = image_gradient::sample_gradient(
+                        &plate_settings.gradient_profile,
+                        degrees,
+                        gradient_pos.x as f32 / square_size.width as f32,
+                        gradient_pos.y as f32 / square_size.height as f32,
+                        seed,
                     );
 
-                    for rlm in &mut continent.realms {
-
-                        let mut realms_agerage: Vec<u8> = Vec::with_capacity(rlm.provinces.len());
-
-                        for pv in &mut rlm.provinces {
-
-                            let mut provinces_agerage: Vec<u8> = Vec::with_capacity(pv.regions.len());
-
-                            for rg in &mut pv.regions {
-                                let gradient_pos =
-                                    Point16::substract(&rg.site_point, &continent.bottom_left);
-                                let pixel_pos = calculate_pixel_pos(&gradient_pos, &per_pixel_size);
-                                let pixel_color = gradient_texture
-                                    .get_pixel(pixel_pos.x as u32, pixel_pos.y as u32);
-
-                                let color_value = pixel_color.0[0];
-                                let new_value = normalize_u8(color_value as f64)
-                                    * continent.elevation as f64;
-                                rg.grey_value = denormalize_u8(new_value);
-
-                                provinces_agerage.push(rg.grey_value);
-                            }
-
-                            let sum: u32 = provinces_agerage.iter().map(|&x| x as u32).sum();
-                            pv.average_grey_value = ((sum as f64) / (provinces_agerage.len() as f64)) as u8;
+                    let tilt = plate_settings.gradient_tilt as f64;
+                    let new_value = (1.0 - tilt + tilt * gradient_value as f64) * continent.elevation as f64;
+                    rg.grey_value = denormalize_u8(new_value);
+                }
+            }
+        }
+    }
 
-                            realms_agerage.push(pv.average_grey_value);
-                        }
+    calculate_average_grey_values(continents);
+}
 
-                        let sum: u32 = realms_agerage.iter().map(|&x| x as u32).sum();
-                        rlm.average_grey_value = ((sum as f64) / (realms_agerage.len() as f64)) as u8;
-                    }
+pub fn calculate_average_grey_values(continents: &mut HashMap<(u16, u16), Continent>) {
+    for continent in continents.values_mut() {
+        for rlm in &mut continent.realms {
+            for pv in &mut rlm.provinces {
+                if pv.regions.is_empty() {
+                    continue;
                 }
-            });
+                let sum: u32 = pv.regions.iter().map(|rg| rg.grey_value as u32).sum();
+                pv.average_grey_value = ((sum as f64) / (pv.regions.len() as f64)) as u8;
+            }
+
+            if rlm.provinces.is_empty() {
+                continue;
+            }
+            let sum: u32 = rlm.provinces.iter().map(|pv| pv.average_grey_value as u32).sum();
+            rlm.average_grey_value = ((sum as f64) / (rlm.provinces.len() as f64)) as u8;
         }
     }
 }
 
-fn get_random_tectonic_elevation() -> f32 {
-    let mut rng = rand::thread_rng();
-    rng.gen_range(0.2..0.7)
+pub fn get_random_tectonic_elevation<R: Rng>(plate_type: PlateType, plate_settings: &PlateSettings, rng: &mut R) -> f32 {
+    match plate_type {
+        // a max below its min is read as the min, like the politics ranges
+        PlateType::Oceanic => rng.gen_range(
+            plate_settings.oceanic_elevation_min
+                ..=plate_settings.oceanic_elevation_max.max(plate_settings.oceanic_elevation_min),
+        ),
+        PlateType::Continental => rng.gen_range(
+            plate_settings.continental_elevation_min
+                ..=plate_settings.continental_elevation_max.max(plate_settings.continental_elevation_min),
+        ),
+    }
 }
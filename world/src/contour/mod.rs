@@ -0,0 +1,270 @@
+use std::collections::HashMap;
+
+pub type Polygon = Vec<(f32, f32)>;
+
+// Edges between two samples are keyed in doubled coordinates: a sample (x, y) sits on
+// (2x, 2y), so the edge to its right neighbour is (2x + 1, 2y) and the one below is (2x, 2y + 1).
+type EdgeKey = (i32, i32);
+
+// Traces the closed outlines where `value` crosses `level` within the samples `min..=max`,
+// marching squares over the grid of sample centers. Samples outside the window count as
+// below the level, so every outline closes. Points are in pixel coordinates, with the
+// sample (x, y) at (x + 0.5, y + 0.5).
+pub fn trace_contours<F>(min: (i32, i32), max: (i32, i32), level: f32, value: F) -> Vec<Polygon>
+where
+    F: Fn(i32, i32) -> f32,
+{
+    let sample = |x: i32, y: i32| -> f32 {
+        if x < min.0 || y < min.1 || x > max.0 || y > max.1 {
+            f32::NEG_INFINITY
+        } else {
+            value(x, y)
+        }
+    };
+
+    let mut segments: Vec<(EdgeKey, EdgeKey)> = Vec::new();
+    for y in min.1 - 1..=max.1 {
+        for x in min.0 - 1..=max.0 {
+            let case = ((sample(x, y) >= level) as u8) << 3
+                | ((sample(x + 1, y) >= level) as u8) << 2
+                | ((sample(x + 1, y + 1) >= level) as u8) << 1
+                | (sample(x, y + 1) >= level) as u8;
+
+            let top = (2 * x + 1, 2 * y);
+            let right = (2 * x + 2, 2 * y + 1);
+            let bottom = (2 * x + 1, 2 * y + 2);
+            let left = (2 * x, 2 * y + 1);
+
+            // saddles keep the two inside corners apart
+            match case {
+                1 | 14 => segments.push((left, bottom)),
+                2 | 13 => segments.push((bottom, right)),
+                3 | 12 => segments.push((left, right)),
+                4 | 11 => segments.push((top, right)),
+                6 | 9 => segments.push((top, bottom)),
+                7 | 8 => segments.push((left, top)),
+                5 => {
+                    segments.push((top, right));
+                    segments.push((left, bottom));
+                }
+                10 => {
+                    segments.push((left, top));
+                    segments.push((bottom, right));
+                }
+                _ => {}
+            }
+        }
+    }
+
+    let position = |key: EdgeKey| -> (f32, f32) {
+        let (x0, y0) = (key.0.div_euclid(2), key.1.div_euclid(2));
+        let (x1, y1) = if key.0 % 2 != 0 { (x0 + 1, y0) } else { (x0, y0 + 1) };
+        let (a, b) = (sample(x0, y0), sample(x1, y1));
+        // outside the window the outline runs halfway between the samples
+        let t = if a.is_finite() && b.is_finite() && a != b {
+            ((level - a) / (b - a)).clamp(0.0, 1.0)
+        } else {
+            0.5
+        };
+        (
+            x0 as f32 + (x1 - x0) as f32 * t + 0.5,
+            y0 as f32 + (y1 - y0) as f32 * t + 0.5,
+        )
+    };
+
+    chain_segments(&segments)
+        .into_iter()
+        .map(|keys| keys.into_iter().map(position).collect())
+        .collect()
+}
+
+// every edge on an outline is shared by exactly two segments, so the loops chain up uniquely
+fn chain_segments(segments: &[(EdgeKey, EdgeKey)]) -> Vec<Vec<EdgeKey>> {
+    let mut by_edge: HashMap<EdgeKey, Vec<usize>> = HashMap::with_capacity(segments.len() * 2);
+    for (i, (a, b)) in segments.iter().enumerate() {
+        by_edge.entry(*a).or_default().push(i);
+        by_edge.entry(*b).or_default().push(i);
+    }
+
+    let mut used: Vec<bool> = vec![false; segments.len()];
+    let mut loops: Vec<Vec<EdgeKey>> = Vec::new();
+    for start in 0..segments.len() {
+        if used[start] {
+            continue;
+        }
+
+        used[start] = true;
+        let first = segments[start].0;
+        let mut current = segments[start].1;
+        let mut keys: Vec<EdgeKey> = vec![first];
+        while current != first {
+            keys.push(current);
+            let next = by_edge[&current].iter().copied().find(|&i| !used[i]);
+            match next {
+                Some(i) => {
+                    used[i] = true;
+                    let (a, b) = segments[i];
+                    current = if a == current { b } else { a };
+                }
+                None => break,
+            }
+        }
+        loops.push(keys);
+    }
+
+    loops
+}
+
+// Douglas-Peucker on a closed outline, split at the point furthest from the first one
+pub fn simplify_polygon(polygon: &[(f32, f32)], tolerance: f32) -> Polygon {
+    if polygon.len() < 4 || tolerance <= 0.0 {
+        return polygon.to_vec();
+    }
+
+    let first = polygon[0];
+    let (split, _) = polygon
+        .iter()
+        .enumerate()
+        .map(|(i, p)| (i, (p.0 - first.0).hypot(p.1 - first.1)))
+        .fold((0, 0.0), |best, next| if next.1 > best.1 { next } else { best });
+
+    let mut closed: Vec<(f32, f32)> = polygon.to_vec();
+    closed.push(first);
+    let mut simplified = simplify_line(&closed[..=split], tolerance);
+    simplified.pop();
+    simplified.extend(simplify_line(&closed[split..], tolerance));
+    simplified.pop();
+    simplified
+}
+
+// Douglas-Peucker on an open line, keeps both end points
+pub fn simplify_line(line: &[(f32, f32)], tolerance: f32) -> Polygon {
+    if line.len() < 3 {
+        return line.to_vec();
+    }
+
+    let mut keep: Vec<bool> = vec![false; line.len()];
+    keep[0] = true;
+    keep[line.len() - 1] = true;
+    let mut stack: Vec<(usize, usize)> = vec![(0, line.len() - 1)];
+
+    while let Some((start, end)) = stack.pop() {
+        let (mut furthest, mut furthest_distance) = (start, 0.0);
+        for i in start + 1..end {
+            let distance = get_segment_distance(line[i], line[start], line[end]);
+            if distance > furthest_distance {
+                furthest = i;
+                furthest_distance = distance;
+            }
+        }
+        if furthest_distance > tolerance {
+            keep[furthest] = true;
+            stack.push((start, furthest));
+            stack.push((furthest, end));
+        }
+    }
+
+    line.iter().zip(keep).filter(|(_, keep)| *keep).map(|(p, _)| *p).collect()
+}
+
+fn get_segment_distance(p: (f32, f32), a: (f32, f32), b: (f32, f32)) -> f32 {
+    let (dx, dy) = (b.0 - a.0, b.1 - a.1);
+    let length_squared = dx * dx + dy * dy;
+    if length_squared <= f32::EPSILON {
+        return (p.0 - a.0).hypot(p.1 - a.1);
+    }
+    let t = (((p.0 - a.0) * dx + (p.1 - a.1) * dy) / length_squared).clamp(0.0, 1.0);
+    (p.0 - (a.0 + t * dx)).hypot(p.1 - (a.1 + t * dy))
+}
+
+// Groups the outlines of one shape into polygons with holes. Outlines nested an even number
+// of times are outer rings, odd ones are holes of the smallest ring around them.
+// Returns the ring indices per polygon, outer ring first.
+pub fn nest_rings(rings: &[Polygon]) -> Vec<Vec<usize>> {
+    let areas: Vec<f32> = rings.iter().map(|ring| get_signed_area(ring).abs()).collect();
+    let containers: Vec<Vec<usize>> = (0..rings.len())
+        .map(|i| {
+            (0..rings.len())
+                .filter(|&j| j != i && !rings[i].is_empty() && contains_point(&rings[j], rings[i][0]))
+                .collect()
+        })
+        .collect();
+
+    let outer: Vec<bool> = containers.iter().map(|containers| containers.len().is_multiple_of(2)).collect();
+    let mut polygons: Vec<Vec<usize>> = Vec::new();
+    let mut polygon_of: Vec<Option<usize>> = vec![None; rings.len()];
+    for i in (0..rings.len()).filter(|&i| outer[i]) {
+        polygon_of[i] = Some(polygons.len());
+        polygons.push(vec![i]);
+    }
+    for (i, containers) in containers.iter().enumerate().filter(|(i, _)| !outer[*i]) {
+        let parent = containers
+            .iter()
+            .copied()
+            .filter(|&j| outer[j])
+            .min_by(|&a, &b| areas[a].total_cmp(&areas[b]));
+        if let Some(parent) = parent {
+            polygons[polygon_of[parent].unwrap()].push(i);
+        }
+    }
+
+    polygons
+}
+
+// shoelace area, positive when the ring turns counter clockwise with y pointing up
+pub fn get_signed_area(ring: &[(f32, f32)]) -> f32 {
+    let mut area = 0.0;
+    for i in 0..ring.len() {
+        let (a, b) = (ring[i], ring[(i + 1) % ring.len()]);
+        area += a.0 * b.1 - b.0 * a.1;
+    }
+    area * 0.5
+}
+
+// even-odd ray cast
+pub fn contains_point(ring: &[(f32, f32)], p: (f32, f32)) -> bool {
+    let mut inside = false;
+    for i in 0..ring.len() {
+        let (a, b) = (ring[i], ring[(i + 1) % ring.len()]);
+        if (a.1 > p.1) != (b.1 > p.1) && p.0 < a.0 + (p.1 - a.1) / (b.1 - a.1) * (b.0 - a.0) {
+            inside = !inside;
+        }
+    }
+    inside
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn square_traces_to_one_closed_outline() {
+        // a 3x3 block of samples inside a 5x5 window
+        let polygons = trace_contours((0, 0), (4, 4), 0.5, |x, y| {
+            if (1..=3).contains(&x) && (1..=3).contains(&y) { 1.0 } else { 0.0 }
+        });
+
+        assert_eq!(polygons.len(), 1);
+        let simplified = simplify_polygon(&polygons[0], 0.1);
+        // the corners are cut diagonally, so the square keeps 8 points
+        assert_eq!(simplified.len(), 8);
+        for p in &simplified {
+            assert!(p.0 >= 1.0 && p.0 <= 4.0 && p.1 >= 1.0 && p.1 <= 4.0, "{:?}", p);
+        }
+    }
+
+    #[test]
+    fn ring_inside_ring_becomes_a_hole() {
+        // a 5x5 block with its center sample cut out
+        let polygons = trace_contours((0, 0), (6, 6), 0.5, |x, y| {
+            let block = (1..=5).contains(&x) && (1..=5).contains(&y);
+            if block && (x, y) != (3, 3) { 1.0 } else { 0.0 }
+        });
+
+        assert_eq!(polygons.len(), 2);
+        let nested = nest_rings(&polygons);
+        assert_eq!(nested.len(), 1);
+        assert_eq!(nested[0].len(), 2);
+        assert!(get_signed_area(&polygons[nested[0][0]]).abs() > get_signed_area(&polygons[nested[0][1]]).abs());
+    }
+}
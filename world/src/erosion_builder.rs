@@ -0,0 +1,239 @@
+use gamescript::models::{
+    continent::{Planet, PlanetSettings},
+    erosion::ErosionSettings,
+    heightmap::Heightmap,
+    point::{denormalize_u8, normalize_u8},
+};
+use rand::{rngs::StdRng, Rng, SeedableRng};
+
+use crate::{
+    continent_builder,
+    progress::{self, ProgressBar, Verbosity},
+};
+
+pub const EROSION_SALT: u64 = 0x5eed_0003;
+
+struct BrushCell {
+    dx: i32,
+    dy: i32,
+    weight: f32,
+}
+
+// Erodes the pixel heightmap with droplets and talus slides, then moves every region
+// by the average change of its pixels. Planets without a heightmap are left as they are.
+pub fn apply_erosion(planet: &mut Planet, planet_settings: &PlanetSettings) {
+    let settings = &planet_settings.erosion_settings;
+    // droplets need the pixel heightmap, turning it off in the heightmap settings turns erosion off
+    let Some(mut heightmap) = planet.heightmap.take() else {
+        progress::log(Verbosity::Normal, format_args!("the planet has no heightmap, erosion is skipped"));
+        return;
+    };
+    let original: Vec<f32> = heightmap.values.clone();
+
+    let mut rng = StdRng::seed_from_u64(planet_settings.seed ^ EROSION_SALT);
+    erode_hydraulic(&mut heightmap, settings, planet_settings.tectonic_settings.sea_level, &mut rng);
+    erode_thermal(&mut heightmap, settings);
+
+    let (lowered, raised) = heightmap.values.iter().zip(&original).fold((0.0, 0.0), |(lowered, raised), (value, before)| {
+        let change = value - before;
+        if change < 0.0 { (lowered - change, raised) } else { (lowered, raised + change) }
+    });
+    progress::log(
+        Verbosity::Debug,
+        format_args!("eroded {:.2}, deposited {:.2} (sum of pixel changes)", lowered, raised),
+    );
+
+    let width = heightmap.size.width as usize;
+    for continent in planet.continents.values_mut() {
+        for rlm in &mut continent.realms {
+            for pv in &mut rlm.provinces {
+                for rg in &mut pv.regions {
+                    if rg.pixels.is_empty() {
+                        continue;
+                    }
+                    let change: f32 = rg.pixels.iter()
+                        .map(|px| {
+                            let i = px.1 as usize * width + px.0 as usize;
+                            heightmap.values[i] - original[i]
+                        })
+                        .sum::<f32>() / rg.pixels.len() as f32;
+                    let value = normalize_u8(rg.grey_value as f64) + change as f64;
+                    rg.grey_value = denormalize_u8(value.clamp(0.0, 1.0));
+                }
+            }
+        }
+    }
+    continent_builder::calculate_average_grey_values(&mut planet.continents);
+
+    planet.heightmap = Some(heightmap);
+}
+
+// Particle erosion: droplets follow the slope with some inertia, take sediment while they
+// speed downhill and drop it when they slow down, climb or evaporate. They stop at the sea.
+fn erode_hydraulic(heightmap: &mut Heightmap, settings: &ErosionSettings, sea_level: f32, rng: &mut StdRng) {
+    let width = heightmap.size.width as usize;
+    let height = heightmap.size.height as usize;
+    if width < 2 || height < 2 {
+        return;
+    }
+    let brush = build_brush(settings.erosion_radius);
+    let mut progress_bar = ProgressBar::new("droplets", settings.droplets as usize);
+
+    for _ in 0..settings.droplets {
+        progress_bar.inc();
+        let mut x: f32 = rng.gen_range(0.0..(width - 1) as f32);
+        let mut y: f32 = rng.gen_range(0.0..(height - 1) as f32);
+        let (mut dir_x, mut dir_y) = (0.0_f32, 0.0_f32);
+        let mut speed: f32 = 1.0;
+        let mut water: f32 = 1.0;
+        let mut sediment: f32 = 0.0;
+
+        for _ in 0..settings.droplet_lifetime {
+            let (node_x, node_y) = (x as usize, y as usize);
+            let (cell_x, cell_y) = (x - node_x as f32, y - node_y as f32);
+            let (current_height, gradient_x, gradient_y) = get_height_and_gradient(heightmap, x, y);
+            if current_height < sea_level {
+                break;
+            }
+
+            dir_x = dir_x * settings.inertia - gradient_x * (1.0 - settings.inertia);
+            dir_y = dir_y * settings.inertia - gradient_y * (1.0 - settings.inertia);
+            let length = dir_x.hypot(dir_y);
+            if length <= f32::EPSILON {
+                break;
+            }
+            dir_x /= length;
+            dir_y /= length;
+            x += dir_x;
+            y += dir_y;
+            if x < 0.0 || y < 0.0 || x >= (width - 1) as f32 || y >= (height - 1) as f32 {
+                break;
+            }
+
+            let new_height = get_height_and_gradient(heightmap, x, y).0;
+            let delta = new_height - current_height;
+            let capacity = (-delta * speed * water * settings.capacity).max(settings.min_capacity);
+
+            if sediment > capacity || delta > 0.0 {
+                // fill the pit when climbing, otherwise drop part of the surplus
+                let amount = if delta > 0.0 {
+                    delta.min(sediment)
+                } else {
+                    (sediment - capacity) * settings.deposition
+                };
+                sediment -= amount;
+                deposit(heightmap, node_x, node_y, cell_x, cell_y, amount);
+            } else {
+                let amount = ((capacity - sediment) * settings.erosion).min(-delta);
+                for cell in &brush {
+                    let bx = node_x as i32 + cell.dx;
+                    let by = node_y as i32 + cell.dy;
+                    if bx < 0 || by < 0 || bx >= width as i32 || by >= height as i32 {
+                        continue;
+                    }
+                    let i = by as usize * width + bx as usize;
+                    let eroded = (amount * cell.weight).min(heightmap.values[i]);
+                    heightmap.values[i] -= eroded;
+                    sediment += eroded;
+                }
+            }
+
+            speed = (speed * speed + delta * settings.gravity).max(0.0).sqrt();
+            water *= 1.0 - settings.evaporation;
+        }
+    }
+}
+
+// Thermal erosion: material above the talus slides to the lower 4 neighbours.
+// Changes are gathered per iteration so the result doesn't depend on the pixel order.
+fn erode_thermal(heightmap: &mut Heightmap, settings: &ErosionSettings) {
+    let width = heightmap.size.width as usize;
+    let height = heightmap.size.height as usize;
+    let mut changes: Vec<f32> = vec![0.0; width * height];
+
+    for _ in 0..settings.thermal_iterations {
+        changes.iter_mut().for_each(|change| *change = 0.0);
+        for y in 0..height {
+            for x in 0..width {
+                let i = y * width + x;
+                let value = heightmap.values[i];
+                let neighbours = [
+                    (x > 0).then(|| i - 1),
+                    (x + 1 < width).then(|| i + 1),
+                    (y > 0).then(|| i - width),
+                    (y + 1 < height).then(|| i + width),
+                ];
+
+                let mut excess_sum = 0.0;
+                let mut max_excess: f32 = 0.0;
+                for &n in neighbours.iter().flatten() {
+                    let excess = value - heightmap.values[n] - settings.talus;
+                    if excess > 0.0 {
+                        excess_sum += excess;
+                        max_excess = max_excess.max(excess);
+                    }
+                }
+                if excess_sum <= 0.0 {
+                    continue;
+                }
+
+                // move half the steepest excess, shared by how much steeper each side is
+                let moved = max_excess * 0.5 * settings.thermal_rate;
+                changes[i] -= moved;
+                for &n in neighbours.iter().flatten() {
+                    let excess = value - heightmap.values[n] - settings.talus;
+                    if excess > 0.0 {
+                        changes[n] += moved * excess / excess_sum;
+                    }
+                }
+            }
+        }
+
+        for (value, change) in heightmap.values.iter_mut().zip(&changes) {
+            *value += change;
+        }
+    }
+}
+
+fn build_brush(radius: u8) -> Vec<BrushCell> {
+    let radius = radius.max(1) as i32;
+    let mut brush: Vec<BrushCell> = Vec::new();
+    for dy in -radius..=radius {
+        for dx in -radius..=radius {
+            let distance = (dx as f32).hypot(dy as f32);
+            if distance < radius as f32 {
+                brush.push(BrushCell { dx, dy, weight: 1.0 - distance / radius as f32 });
+            }
+        }
+    }
+    let weight_sum: f32 = brush.iter().map(|cell| cell.weight).sum();
+    brush.iter_mut().for_each(|cell| cell.weight /= weight_sum);
+    brush
+}
+
+// bilinear height and slope within the cell of the top left node
+fn get_height_and_gradient(heightmap: &Heightmap, x: f32, y: f32) -> (f32, f32, f32) {
+    let (node_x, node_y) = (x as u16, y as u16);
+    let (u, v) = (x - node_x as f32, y - node_y as f32);
+    let nw = heightmap.get(node_x, node_y);
+    let ne = heightmap.get(node_x + 1, node_y);
+    let sw = heightmap.get(node_x, node_y + 1);
+    let se = heightmap.get(node_x + 1, node_y + 1);
+
+    let gradient_x = (ne - nw) * (1.0 - v) + (se - sw) * v;
+    let gradient_y = (sw - nw) * (1.0 - u) + (se - ne) * u;
+    let value = nw * (1.0 - u) * (1.0 - v) + ne * u * (1.0 - v) + sw * (1.0 - u) * v + se * u * v;
+    (value, gradient_x, gradient_y)
+}
+
+fn deposit(heightmap: &mut Heightmap, node_x: usize, node_y: usize, u: f32, v: f32, amount: f32) {
+    let (x, y) = (node_x as u16, node_y as u16);
+    let add = |heightmap: &mut Heightmap, x: u16, y: u16, weight: f32| {
+        let value = heightmap.get(x, y);
+        heightmap.set(x, y, value + amount * weight);
+    };
+    add(heightmap, x, y, (1.0 - u) * (1.0 - v));
+    add(heightmap, x + 1, y, u * (1.0 - v));
+    add(heightmap, x, y + 1, (1.0 - u) * v);
+    add(heightmap, x + 1, y + 1, u * v);
+}
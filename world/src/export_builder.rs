@@ -0,0 +1,185 @@
+use gamescript::{
+    file_read_write, json_read_write,
+    models::{
+        continent::{Planet, PlanetSettings},
+        export::{ExportSettings, NormalConvention, Resampling},
+        heightmap::{self, Heightmap},
+        point::Size16,
+    },
+};
+use image::{ImageBuffer, Luma, Rgb};
+use serde::Serialize;
+
+use crate::adjacency;
+
+// Everything a terrain tool needs to turn the exported values back into metres.
+#[derive(Serialize)]
+struct HeightmapSidecar {
+    width: u16,
+    height: u16,
+    resampling: Resampling,
+    // metres of the lowest and highest exported value, the png and r16 span this range
+    min_metres: f32,
+    max_metres: f32,
+    sea_level_metres: f32,
+    // world metres covered by one exported pixel, x and y differ when the aspect changes
+    metres_per_pixel: [f32; 2],
+    normal_convention: NormalConvention,
+    files: Vec<String>,
+}
+
+// Writes the planet elevation at the export size as 16-bit PNG, raw r16 and raw r32 files, its
+// normal, slope and aspect maps, and a JSON sidecar describing their range and scale.
+pub fn export_heightmap(planet: &Planet, planet_settings: &PlanetSettings, dist_folder: &str) {
+    let settings = &planet_settings.export_settings;
+    let sea_level = planet_settings.tectonic_settings.sea_level;
+    let heightmap = get_export_heightmap(planet, settings);
+    let (width, height) = (heightmap.size.width, heightmap.size.height);
+
+    let metres: Vec<f32> = heightmap.values.iter().map(|&value| settings.get_metres(value, sea_level)).collect();
+    let (min, max) = metres
+        .iter()
+        .fold((f32::INFINITY, f32::NEG_INFINITY), |(min, max), &value| (min.min(value), max.max(value)));
+    let range = (max - min).max(f32::EPSILON);
+    let normalized: Vec<u16> =
+        metres.iter().map(|&value| ((value - min) / range * u16::MAX as f32).round() as u16).collect();
+
+    let mut files: Vec<String> = Vec::new();
+    if settings.png16 {
+        let image: ImageBuffer<Luma<u16>, Vec<u16>> =
+            ImageBuffer::from_raw(width as u32, height as u32, normalized.clone()).unwrap();
+        image.save(format!("{}\\{}", dist_folder, "14__heightmap.png")).unwrap();
+        files.push(String::from("14__heightmap.png"));
+    }
+    if settings.r16 {
+        let bytes: Vec<u8> = normalized.iter().flat_map(|value| value.to_le_bytes()).collect();
+        file_read_write::write_bytes(bytes, &format!("{}\\{}", dist_folder, "14__heightmap.r16"));
+        files.push(String::from("14__heightmap.r16"));
+    }
+    if settings.r32 {
+        let bytes: Vec<u8> = metres.iter().flat_map(|value| value.to_le_bytes()).collect();
+        file_read_write::write_bytes(bytes, &format!("{}\\{}", dist_folder, "14__heightmap.r32"));
+        files.push(String::from("14__heightmap.r32"));
+    }
+    if settings.normal_map || settings.slope_map {
+        files.extend(export_surface_maps(&heightmap, planet, settings, dist_folder));
+    }
+
+    let sidecar = HeightmapSidecar {
+        width,
+        height,
+        resampling: settings.resampling,
+        min_metres: min,
+        max_metres: max,
+        sea_level_metres: 0.0,
+        metres_per_pixel: [
+            settings.pixel_size * planet.img_size.width as f32 / width as f32,
+            settings.pixel_size * planet.img_size.height as f32 / height as f32,
+        ],
+        normal_convention: settings.normal_convention,
+        files,
+    };
+    json_read_write::write(&sidecar, &format!("{}\\{}", dist_folder, "14__heightmap.json"));
+}
+
+// Normal, slope and aspect of every exported pixel. The height scale is given in planet pixels,
+// so a resized export keeps the slopes of the planet.
+fn export_surface_maps(heightmap: &Heightmap, planet: &Planet, settings: &ExportSettings, dist_folder: &str) -> Vec<String> {
+    let (width, height) = (heightmap.size.width, heightmap.size.height);
+    let height_scale = get_height_scales(settings.height_scale, &planet.img_size, &heightmap.size);
+    let green = match settings.normal_convention {
+        NormalConvention::OpenGl => 1.0,
+        NormalConvention::DirectX => -1.0,
+    };
+
+    let mut normals: ImageBuffer<Rgb<u8>, Vec<u8>> = ImageBuffer::new(width as u32, height as u32);
+    let mut slopes: ImageBuffer<Luma<u16>, Vec<u16>> = ImageBuffer::new(width as u32, height as u32);
+    let mut aspects: ImageBuffer<Luma<u16>, Vec<u16>> = ImageBuffer::new(width as u32, height as u32);
+    for y in 0..height {
+        for x in 0..width {
+            let normal = heightmap.get_normal(x, y, height_scale, settings.edges);
+            let encode = |value: f32| ((value * 0.5 + 0.5) * u8::MAX as f32).round() as u8;
+            normals.put_pixel(x as u32, y as u32, Rgb([encode(normal[0]), encode(normal[1] * green), encode(normal[2])]));
+
+            let (slope, aspect) = heightmap::get_slope_and_aspect(normal);
+            slopes.put_pixel(x as u32, y as u32, Luma([(slope / 90.0 * u16::MAX as f32).round() as u16]));
+            aspects.put_pixel(x as u32, y as u32, Luma([(aspect / 360.0 * u16::MAX as f32).round() as u16]));
+        }
+    }
+
+    let mut files: Vec<String> = Vec::new();
+    if settings.normal_map {
+        normals.save(format!("{}\\{}", dist_folder, "14__normals.png")).unwrap();
+        files.push(String::from("14__normals.png"));
+    }
+    if settings.slope_map {
+        slopes.save(format!("{}\\{}", dist_folder, "14__slope.png")).unwrap();
+        aspects.save(format!("{}\\{}", dist_folder, "14__aspect.png")).unwrap();
+        files.extend([String::from("14__slope.png"), String::from("14__aspect.png")]);
+    }
+    files
+}
+
+// the height scale in export pixels along x and y, an export stretched more one way than the
+// other has its pixels covering less of the planet along that axis
+fn get_height_scales(height_scale: f32, planet_size: &Size16, export_size: &Size16) -> [f32; 2] {
+    [
+        height_scale * export_size.width as f32 / planet_size.width as f32,
+        height_scale * export_size.height as f32 / planet_size.height as f32,
+    ]
+}
+
+// the planet heightmap, or the flat region elevations when it wasn't built, at the export size
+fn get_export_heightmap(planet: &Planet, settings: &ExportSettings) -> Heightmap {
+    let size = Size16::new(
+        if settings.width == 0 { planet.img_size.width } else { settings.width },
+        if settings.height == 0 { planet.img_size.height } else { settings.height },
+    );
+
+    let regions_heightmap;
+    let heightmap = match &planet.heightmap {
+        Some(heightmap) => heightmap,
+        None => {
+            let mut flat = Heightmap::new(Size16::new(planet.img_size.width, planet.img_size.height));
+            let keys = adjacency::get_sorted_continent_keys(&planet.continents);
+            for rg in adjacency::get_sorted_regions(&planet.continents, &keys) {
+                for &(x, y) in &rg.pixels {
+                    flat.set(x, y, rg.grey_value as f32 / u8::MAX as f32);
+                }
+            }
+            regions_heightmap = flat;
+            &regions_heightmap
+        }
+    };
+
+    if size == heightmap.size {
+        return Heightmap { size, values: heightmap.values.clone() };
+    }
+    heightmap.resize(size, settings.resampling)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use gamescript::models::heightmap::EdgeMode;
+
+    #[test]
+    fn stretched_export_keeps_the_planet_slopes() {
+        // a plane rising to the east and to the south alike, exported twice as wide and half as high
+        let mut heightmap = Heightmap::new(Size16::new(16, 16));
+        for y in 0..16 {
+            for x in 0..16 {
+                heightmap.set(x, y, (x + y) as f32 / 32.0);
+            }
+        }
+        let exported = heightmap.resize(Size16::new(32, 8), Resampling::Bilinear);
+
+        let expected = heightmap.get_normal(8, 8, [10.0; 2], EdgeMode::Clamp);
+        let height_scale = get_height_scales(10.0, &heightmap.size, &exported.size);
+        assert_eq!(height_scale, [20.0, 5.0]);
+        let normal = exported.get_normal(16, 4, height_scale, EdgeMode::Clamp);
+        for (value, expected) in normal.iter().zip(expected) {
+            assert!((value - expected).abs() < 1e-4, "{:?} {:?}", normal, expected);
+        }
+    }
+}
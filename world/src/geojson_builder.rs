@@ -0,0 +1,236 @@
+use std::collections::BTreeMap;
+
+use gamescript::{
+    json_read_write,
+    models::{
+        climate::Biome,
+        continent::{MapLevel, Planet, PlanetSettings},
+        point::Size16,
+        resource::{Resource, Resources},
+        vector::GeoCoordinates,
+    },
+};
+use serde::Serialize;
+use world::contour::{self, Polygon};
+
+use crate::{adjacency, vector_builder};
+
+// The levels written as GeoJSON, each into its own file.
+pub const GEOJSON_LEVELS: [MapLevel; 3] = [MapLevel::Province, MapLevel::Realm, MapLevel::Continent];
+
+#[derive(Serialize)]
+struct FeatureCollection {
+    #[serde(rename = "type")]
+    kind: &'static str,
+    features: Vec<Feature>,
+}
+
+#[derive(Serialize)]
+struct Feature {
+    #[serde(rename = "type")]
+    kind: &'static str,
+    id: u32,
+    geometry: Geometry,
+    properties: FeatureProperties,
+}
+
+#[derive(Serialize)]
+struct Geometry {
+    #[serde(rename = "type")]
+    kind: &'static str,
+    // polygons, each an outer ring followed by its holes
+    coordinates: Vec<Vec<Vec<[f64; 2]>>>,
+}
+
+#[derive(Serialize)]
+struct FeatureProperties {
+    level: &'static str,
+    id: u32,
+    name: String,
+    // realm of a province, continent of a realm
+    parent_id: Option<u32>,
+    grid_coord: [u16; 2],
+    pixels: u32,
+    // elevation in 0..1 like the heightmap
+    elevation_min: f32,
+    elevation_max: f32,
+    elevation_mean: f32,
+    land_share: f32,
+    biome: Biome,
+    average_temperature: f32,
+    average_precipitation: f32,
+    // summed over the regions, keyed by resource name
+    resources: BTreeMap<&'static str, f32>,
+}
+
+// Everything a feature needs besides its outline, gathered in the same walk as the owner raster.
+struct CellStats {
+    name: String,
+    parent_id: Option<u32>,
+    grid_coord: [u16; 2],
+    pixels: u32,
+    elevation_min: f32,
+    elevation_max: f32,
+    elevation_sum: f64,
+    land_pixels: u32,
+    biome_pixels: [u32; Biome::ALL.len()],
+    temperature_sum: f64,
+    precipitation_sum: f64,
+    resources: Resources,
+}
+
+impl CellStats {
+    fn new(name: &str, parent_id: Option<u32>, grid_coord: [u16; 2]) -> CellStats {
+        CellStats {
+            name: name.to_string(),
+            parent_id,
+            grid_coord,
+            pixels: 0,
+            elevation_min: f32::MAX,
+            elevation_max: f32::MIN,
+            elevation_sum: 0.0,
+            land_pixels: 0,
+            biome_pixels: [0; Biome::ALL.len()],
+            temperature_sum: 0.0,
+            precipitation_sum: 0.0,
+            resources: Resources::default(),
+        }
+    }
+}
+
+// Writes one FeatureCollection per level, named like `8__provinces.geojson`.
+pub fn build_planet_geojson(planet: &Planet, planet_settings: &PlanetSettings, dist_folder: &str) {
+    for level in GEOJSON_LEVELS {
+        let collection = build_feature_collection(planet, planet_settings, level);
+        let path = &format!("{}\\8__{}s.geojson", dist_folder, level.name());
+        json_read_write::write(&collection, path);
+    }
+}
+
+fn build_feature_collection(planet: &Planet, planet_settings: &PlanetSettings, level: MapLevel) -> FeatureCollection {
+    let settings = &planet_settings.vector_settings;
+    let stats = build_cell_stats(planet, planet_settings, level);
+    let boundaries = vector_builder::build_boundaries(planet, level, settings.tolerance);
+
+    let features = boundaries
+        .iter()
+        .map(|boundary| {
+            let cell = &stats[boundary.id as usize];
+            let pixels = cell.pixels.max(1) as f64;
+            let biome = Biome::ALL
+                .iter()
+                .zip(cell.biome_pixels)
+                .fold((Biome::Ocean, 0), |best, (biome, count)| if count > best.1 { (*biome, count) } else { best })
+                .0;
+
+            Feature {
+                kind: "Feature",
+                id: boundary.id,
+                geometry: build_geometry(&boundary.polygons, &planet.img_size, settings.geojson_coordinates),
+                properties: FeatureProperties {
+                    level: level.name(),
+                    id: boundary.id,
+                    name: cell.name.clone(),
+                    parent_id: cell.parent_id,
+                    grid_coord: cell.grid_coord,
+                    pixels: cell.pixels,
+                    elevation_min: cell.elevation_min,
+                    elevation_max: cell.elevation_max,
+                    elevation_mean: (cell.elevation_sum / pixels) as f32,
+                    land_share: (cell.land_pixels as f64 / pixels) as f32,
+                    biome,
+                    average_temperature: (cell.temperature_sum / pixels) as f32,
+                    average_precipitation: (cell.precipitation_sum / pixels) as f32,
+                    resources: Resource::ALL.iter().map(|&resource| (resource.name(), cell.resources.get(resource))).collect(),
+                },
+            }
+        })
+        .collect();
+
+    FeatureCollection { kind: "FeatureCollection", features }
+}
+
+fn build_cell_stats(planet: &Planet, planet_settings: &PlanetSettings, level: MapLevel) -> Vec<CellStats> {
+    let elevations = planet.get_pixel_elevations();
+    let sea_level = planet_settings.tectonic_settings.sea_level;
+    let width = planet.img_size.width as usize;
+    let keys = adjacency::get_sorted_continent_keys(&planet.continents);
+
+    let mut stats: Vec<CellStats> = Vec::new();
+    let mut realm_id: u32 = 0;
+    for (continent_id, key) in keys.iter().enumerate() {
+        let continent = &planet.continents[key];
+        if level == MapLevel::Continent {
+            stats.push(CellStats::new(&continent.name, None, [key.0, key.1]));
+        }
+        for rlm in &continent.realms {
+            if level == MapLevel::Realm {
+                stats.push(CellStats::new(&rlm.name, Some(continent_id as u32), [rlm.grid_coord.x, rlm.grid_coord.y]));
+            }
+            for pv in &rlm.provinces {
+                if level == MapLevel::Province {
+                    stats.push(CellStats::new(&pv.name, Some(realm_id), [pv.grid_coord.x, pv.grid_coord.y]));
+                }
+                let cell = stats.last_mut().unwrap();
+                for rg in &pv.regions {
+                    let biome = Biome::ALL.iter().position(|biome| *biome == rg.biome).unwrap();
+                    let pixels = rg.pixels.len() as u32;
+                    cell.pixels += pixels;
+                    cell.biome_pixels[biome] += pixels;
+                    cell.temperature_sum += rg.temperature as f64 * pixels as f64;
+                    cell.precipitation_sum += rg.precipitation as f64 * pixels as f64;
+                    cell.resources.add(&rg.resources);
+                    for px in &rg.pixels {
+                        let elevation = elevations[px.1 as usize * width + px.0 as usize];
+                        cell.elevation_min = cell.elevation_min.min(elevation);
+                        cell.elevation_max = cell.elevation_max.max(elevation);
+                        cell.elevation_sum += elevation as f64;
+                        cell.land_pixels += (elevation >= sea_level) as u32;
+                    }
+                }
+            }
+            realm_id += 1;
+        }
+    }
+    stats
+}
+
+// GeoJSON wants closed rings, outer rings counter clockwise and holes clockwise
+fn build_geometry(polygons: &[Polygon], img_size: &Size16, coordinates: GeoCoordinates) -> Geometry {
+    let project = |p: &(f32, f32)| -> (f32, f32) {
+        match coordinates {
+            GeoCoordinates::Pixel => *p,
+            GeoCoordinates::LonLat => (
+                p.0 / img_size.width as f32 * 360.0 - 180.0,
+                90.0 - p.1 / img_size.height as f32 * 180.0,
+            ),
+        }
+    };
+    let rings: Vec<Polygon> = polygons.iter().map(|polygon| polygon.iter().map(project).collect()).collect();
+
+    let coordinates = contour::nest_rings(&rings)
+        .iter()
+        .map(|polygon| {
+            polygon
+                .iter()
+                .enumerate()
+                .map(|(i, &ring)| {
+                    let mut ring: Polygon = rings[ring].clone();
+                    let outer = i == 0;
+                    if (contour::get_signed_area(&ring) > 0.0) != outer {
+                        ring.reverse();
+                    }
+                    ring.push(ring[0]);
+                    ring.iter().map(|p| [round_coordinate(p.0), round_coordinate(p.1)]).collect()
+                })
+                .collect()
+        })
+        .collect();
+
+    Geometry { kind: "MultiPolygon", coordinates }
+}
+
+// five decimals is about a metre on earth, far below a pixel
+fn round_coordinate(value: f32) -> f64 {
+    (value as f64 * 100_000.0).round() / 100_000.0
+}
@@ -0,0 +1,95 @@
+use gamescript::models::{
+    continent::{Continent, PlanetSettings},
+    heightmap::Heightmap,
+    point::{normalize_u8, Size16},
+};
+use std::collections::HashMap;
+
+use crate::{adjacency, noise_builder, progress::ProgressBar};
+
+struct Site {
+    x: f32,
+    y: f32,
+    value: f32,
+}
+
+// Interpolates the region values into a continuous heightmap and adds the pixel detail noise.
+// Every pixel blends the sites within `radius` with Franke-Little inverse distance weights,
+// which fall to zero at the radius so the surface has no seams along region borders.
+pub fn build_heightmap(
+    continents: &HashMap<(u16, u16), Continent>,
+    planet_settings: &PlanetSettings,
+) -> Heightmap {
+    let img_size = &planet_settings.img_size;
+    let settings = &planet_settings.heightmap_settings;
+    let radius = settings.radius.max(1.0);
+    let pixel_noise = noise_builder::build_pixel_noise(planet_settings);
+
+    // bucket sites on a grid of radius sized cells so a pixel only checks its 3x3 cells
+    let buckets_width = (img_size.width as f32 / radius).ceil() as usize;
+    let buckets_height = (img_size.height as f32 / radius).ceil() as usize;
+    let mut buckets: Vec<Vec<Site>> = (0..buckets_width * buckets_height).map(|_| Vec::new()).collect();
+    // sorted, so the weights of a pixel are summed in the same order every run
+    let keys = adjacency::get_sorted_continent_keys(continents);
+    for rg in adjacency::get_sorted_regions(continents, &keys) {
+        let (x, y) = (rg.site_point.x as f32, rg.site_point.y as f32);
+        let bx = ((x / radius) as usize).min(buckets_width - 1);
+        let by = ((y / radius) as usize).min(buckets_height - 1);
+        buckets[by * buckets_width + bx].push(Site {
+            x,
+            y,
+            value: normalize_u8(rg.grey_value as f64) as f32,
+        });
+    }
+
+    let mut heightmap = Heightmap::new(Size16::new(img_size.width, img_size.height));
+    let mut progress_bar = ProgressBar::new("heightmap", img_size.height as usize);
+    for y in 0..img_size.height {
+        progress_bar.inc();
+        for x in 0..img_size.width {
+            let (px, py) = (x as f32, y as f32);
+            let bx = (px / radius) as usize;
+            let by = (py / radius) as usize;
+
+            let mut weight_sum = 0.0;
+            let mut value_sum = 0.0;
+            let mut exact_value: Option<f32> = None;
+            let mut nearest_distance = f32::INFINITY;
+            let mut nearest_value = 0.0;
+
+            for cy in by.saturating_sub(1)..(by + 2).min(buckets_height) {
+                for cx in bx.saturating_sub(1)..(bx + 2).min(buckets_width) {
+                    for site in &buckets[cy * buckets_width + cx] {
+                        let distance = (site.x - px).hypot(site.y - py);
+                        if distance < nearest_distance {
+                            nearest_distance = distance;
+                            nearest_value = site.value;
+                        }
+                        if distance >= radius {
+                            continue;
+                        }
+                        if distance < f32::EPSILON {
+                            exact_value = Some(site.value);
+                            continue;
+                        }
+
+                        let weight = ((radius - distance) / (radius * distance)).powf(settings.power);
+                        weight_sum += weight;
+                        value_sum += weight * site.value;
+                    }
+                }
+            }
+
+            let value = match exact_value {
+                Some(value) => value,
+                None if weight_sum > 0.0 => value_sum / weight_sum,
+                None => nearest_value,
+            };
+
+            let detail = pixel_noise.sample(px, py) * planet_settings.pixel_noise.weight;
+            heightmap.set(x, y, (value + detail).clamp(0.0, 1.0));
+        }
+    }
+
+    heightmap
+}
@@ -0,0 +1,294 @@
+use gamescript::models::{
+    continent::{Planet, PlanetSettings, Region},
+    hydrology::{Hydrology, Lake, River},
+};
+use std::{cmp::Ordering, collections::BinaryHeap};
+
+use crate::adjacency;
+
+// smallest drop between a filled region and the one it drains into
+const FILL_EPSILON: f32 = 1e-5;
+
+struct Cell {
+    elevation: f32,
+    region: usize,
+}
+
+impl PartialEq for Cell {
+    fn eq(&self, other: &Self) -> bool {
+        self.elevation == other.elevation
+    }
+}
+
+impl Eq for Cell {}
+
+impl PartialOrd for Cell {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for Cell {
+    // reversed, so the BinaryHeap pops the lowest region first
+    fn cmp(&self, other: &Self) -> Ordering {
+        other
+            .elevation
+            .total_cmp(&self.elevation)
+            .then_with(|| other.region.cmp(&self.region))
+    }
+}
+
+// Routes rainfall over the region adjacency graph down to the sea. Depressions are filled
+// with a priority flood, filled regions deeper than `lake_min_depth` become lakes, and regions
+// collecting more than `discharge_threshold` carry rivers, split into Strahler order segments.
+pub fn build_hydrology(planet: &Planet, planet_settings: &PlanetSettings) -> Hydrology {
+    let settings = &planet_settings.hydrology_settings;
+    let sea_level = planet_settings.tectonic_settings.sea_level;
+
+    let keys = adjacency::get_sorted_continent_keys(&planet.continents);
+    let regions = adjacency::get_sorted_regions(&planet.continents, &keys);
+    let (owners, regions_len) =
+        adjacency::build_regions_owner_raster(&planet.img_size, &planet.continents, &keys);
+    let adjacency = adjacency::build_adjacency(&planet.img_size, &owners, regions_len, true);
+
+    let elevations: Vec<f32> = regions.iter().map(|rg| planet.get_region_elevation(rg)).collect();
+    let is_sea: Vec<bool> = elevations.iter().map(|&elevation| elevation < sea_level).collect();
+
+    let (filled, receivers, order) = fill_depressions(&elevations, &is_sea, &adjacency);
+
+    // rain falls on land only, bigger regions catch more of it
+    let average_area = regions.iter().map(|rg| rg.pixels.len()).sum::<usize>() as f32 / regions_len.max(1) as f32;
+    let mut accumulation: Vec<f32> = regions
+        .iter()
+        .zip(&is_sea)
+        .map(|(rg, &sea)| if sea { 0.0 } else { settings.rainfall * rg.pixels.len() as f32 / average_area })
+        .collect();
+    // receivers are popped before the regions draining into them
+    for &region in order.iter().rev() {
+        if let Some(receiver) = receivers[region] {
+            accumulation[receiver] += accumulation[region];
+        }
+    }
+
+    let lakes = find_lakes(&elevations, &filled, &is_sea, &adjacency, settings.lake_min_depth);
+    let is_river: Vec<bool> = (0..regions_len)
+        .map(|i| !is_sea[i] && accumulation[i] >= settings.discharge_threshold)
+        .collect();
+    let width = planet.img_size.width as i32;
+    let rivers = extract_rivers(&regions, &receivers, &order, &accumulation, &is_river, width);
+
+    Hydrology {
+        flow_directions: receivers.iter().map(|receiver| receiver.map(|r| r as u32)).collect(),
+        flow_accumulation: accumulation,
+        rivers,
+        lakes,
+    }
+}
+
+// Priority flood from the sea: every region drains into the neighbour it was reached from,
+// and is raised to just above it when it sits in a depression.
+// Returns the filled elevations, the receivers and the order regions were reached in.
+fn fill_depressions(
+    elevations: &[f32],
+    is_sea: &[bool],
+    adjacency: &[Vec<usize>],
+) -> (Vec<f32>, Vec<Option<usize>>, Vec<usize>) {
+    let len = elevations.len();
+    let mut filled: Vec<f32> = elevations.to_vec();
+    let mut receivers: Vec<Option<usize>> = vec![None; len];
+    let mut visited: Vec<bool> = vec![false; len];
+    let mut order: Vec<usize> = Vec::with_capacity(len);
+    let mut heap: BinaryHeap<Cell> = BinaryHeap::new();
+
+    for i in (0..len).filter(|&i| is_sea[i]) {
+        visited[i] = true;
+        heap.push(Cell { elevation: elevations[i], region: i });
+    }
+    // a planet without sea drains into its lowest region
+    if heap.is_empty() {
+        if let Some(lowest) = (0..len).min_by(|&a, &b| elevations[a].total_cmp(&elevations[b])) {
+            visited[lowest] = true;
+            heap.push(Cell { elevation: elevations[lowest], region: lowest });
+        }
+    }
+
+    while let Some(cell) = heap.pop() {
+        order.push(cell.region);
+        for &neighbour in &adjacency[cell.region] {
+            if visited[neighbour] {
+                continue;
+            }
+            visited[neighbour] = true;
+            filled[neighbour] = elevations[neighbour].max(filled[cell.region] + FILL_EPSILON);
+            receivers[neighbour] = Some(cell.region);
+            heap.push(Cell { elevation: filled[neighbour], region: neighbour });
+        }
+    }
+
+    (filled, receivers, order)
+}
+
+fn find_lakes(
+    elevations: &[f32],
+    filled: &[f32],
+    is_sea: &[bool],
+    adjacency: &[Vec<usize>],
+    lake_min_depth: f32,
+) -> Vec<Lake> {
+    let is_lake: Vec<bool> = (0..elevations.len())
+        .map(|i| !is_sea[i] && filled[i] - elevations[i] > lake_min_depth)
+        .collect();
+    let mut visited: Vec<bool> = vec![false; elevations.len()];
+    let mut lakes: Vec<Lake> = Vec::new();
+
+    for start in 0..elevations.len() {
+        if !is_lake[start] || visited[start] {
+            continue;
+        }
+
+        visited[start] = true;
+        let mut regions: Vec<u32> = Vec::new();
+        let mut stack: Vec<usize> = vec![start];
+        let mut surface: f32 = 0.0;
+        while let Some(region) = stack.pop() {
+            regions.push(region as u32);
+            surface = surface.max(filled[region]);
+            for &neighbour in &adjacency[region] {
+                if is_lake[neighbour] && !visited[neighbour] {
+                    visited[neighbour] = true;
+                    stack.push(neighbour);
+                }
+            }
+        }
+
+        regions.sort_unstable();
+        lakes.push(Lake { regions, surface });
+    }
+
+    lakes
+}
+
+fn extract_rivers(
+    regions: &[&Region],
+    receivers: &[Option<usize>],
+    order: &[usize],
+    accumulation: &[f32],
+    is_river: &[bool],
+    width: i32,
+) -> Vec<River> {
+    let len = regions.len();
+
+    // strahler order, upstream regions are handled before the ones they drain into
+    let mut strahler: Vec<u8> = vec![0; len];
+    let mut upstream_max: Vec<u8> = vec![0; len];
+    let mut upstream_max_count: Vec<u8> = vec![0; len];
+    for &region in order.iter().rev() {
+        if !is_river[region] {
+            continue;
+        }
+        strahler[region] = match (upstream_max[region], upstream_max_count[region]) {
+            (0, _) => 1,
+            (max, count) if count >= 2 => max + 1,
+            (max, _) => max,
+        };
+        if let Some(receiver) = receivers[region].filter(|&r| is_river[r]) {
+            match strahler[region].cmp(&upstream_max[receiver]) {
+                Ordering::Greater => {
+                    upstream_max[receiver] = strahler[region];
+                    upstream_max_count[receiver] = 1;
+                }
+                Ordering::Equal => upstream_max_count[receiver] += 1,
+                Ordering::Less => {}
+            }
+        }
+    }
+
+    // a segment starts at a source or where a confluence raised the order
+    let mut rivers: Vec<River> = Vec::new();
+    let mut river_of_region: Vec<Option<u32>> = vec![None; len];
+    for start in (0..len).filter(|&i| is_river[i] && upstream_max[i] != strahler[i]) {
+        let mut path: Vec<usize> = vec![start];
+        let mut current = start;
+        while let Some(next) = receivers[current] {
+            path.push(next);
+            if !is_river[next] || strahler[next] != strahler[start] {
+                break;
+            }
+            current = next;
+        }
+
+        // `current` is the last region of the segment, the path may end in the region it flows into
+        let joins = *path.last().unwrap() != current;
+        let own_len = if joins { path.len() - 1 } else { path.len() };
+        for &region in &path[..own_len] {
+            river_of_region[region] = Some(rivers.len() as u32);
+        }
+        rivers.push(River {
+            regions: path.iter().map(|&i| i as u32).collect(),
+            pixels: build_river_pixels(regions, &path, is_river, width),
+            strahler_order: strahler[start],
+            discharge: accumulation[current],
+            flows_into: None,
+        });
+    }
+
+    for river in &mut rivers {
+        let last = *river.regions.last().unwrap() as usize;
+        if is_river[last] && river_of_region[last] != river_of_region[river.regions[0] as usize] {
+            river.flows_into = river_of_region[last];
+        }
+    }
+
+    rivers
+}
+
+// straight lines between the region sites, a mouth ends halfway to the sea region site
+fn build_river_pixels(regions: &[&Region], path: &[usize], is_river: &[bool], width: i32) -> Vec<(u16, u16)> {
+    let mut pixels: Vec<(u16, u16)> = vec![(regions[path[0]].site_point.x, regions[path[0]].site_point.y)];
+
+    for pair in path.windows(2) {
+        let from = &regions[pair[0]].site_point;
+        let to = &regions[pair[1]].site_point;
+        let (mut x1, mut y1) = (get_nearest_x(from.x as i32, to.x as i32, width), to.y as i32);
+        if !is_river[pair[1]] {
+            x1 = (from.x as i32 + x1).div_euclid(2);
+            y1 = (from.y as i32 + y1) / 2;
+        }
+        trace_line(from.x as i32, from.y as i32, x1, y1, width, &mut pixels);
+    }
+
+    pixels
+}
+
+// the copy of x1 one map width to the east or west that is closest to x0, the map wraps
+fn get_nearest_x(x0: i32, x1: i32, width: i32) -> i32 {
+    if x1 - x0 > width / 2 {
+        x1 - width
+    } else if x0 - x1 > width / 2 {
+        x1 + width
+    } else {
+        x1
+    }
+}
+
+// bresenham the shorter way around the map, skips the first point which is already the end
+// of the previous line
+pub fn trace_line(x0: i32, y0: i32, x1: i32, y1: i32, width: i32, pixels: &mut Vec<(u16, u16)>) {
+    let x1 = get_nearest_x(x0, x1, width);
+    let (dx, dy) = ((x1 - x0).abs(), -(y1 - y0).abs());
+    let (sx, sy) = (if x0 < x1 { 1 } else { -1 }, if y0 < y1 { 1 } else { -1 });
+    let (mut x, mut y, mut error) = (x0, y0, dx + dy);
+
+    while x != x1 || y != y1 {
+        let doubled = 2 * error;
+        if doubled >= dy {
+            error += dy;
+            x += sx;
+        }
+        if doubled <= dx {
+            error += dx;
+            y += sy;
+        }
+        pixels.push((x.rem_euclid(width) as u16, y as u16));
+    }
+}
@@ -1,42 +1,43 @@
 use rand::Rng;
-use image::{GrayImage, DynamicImage};
-extern crate image;
-use std::path::Path;
+use gamescript::models::tectonics::GradientProfile;
+use crate::noise;
 
-pub fn get_degrees_by_index(index: u8) -> u16 {
-    match index {
-        0 => return 0,
-        1 => return 22,
-        2 => return 45,
-        3 => return 67,
-        4 => return 90,
-        5 => return 112,
-        6 => return 135,
-        7 => return 157,
-        8 => return 180,
-        9 => return 202,
-        10 => return 225,
-        11 => return 247,
-        12 => return 270,
-        13 => return 292,
-        14 => return 315,
-        15 => return 337,
-        16 => return 337,
-        _ => return 337,
-    }
+// plate directions stay an index into these steps, planet.bin stores them as a u8
+pub const DIRECTIONS_LEN: u8 = 16;
+
+// directions are evenly spread, 0 points east and they turn counter clockwise
+pub fn get_degrees_by_index(index: u8) -> f32 {
+    (index % DIRECTIONS_LEN) as f32 * (360.0 / DIRECTIONS_LEN as f32)
 }
 
-pub fn get_random_degrees_index() -> u8 {
-    let mut rng = rand::thread_rng();
-    rng.gen_range(0..16)
+pub fn get_random_degrees_index<R: Rng>(rng: &mut R) -> u8 {
+    rng.gen_range(0..DIRECTIONS_LEN)
 }
 
-pub fn load_gradient(nr: u16) -> GrayImage {
+// Value of the gradient at (u, v), both in 0..1 with the origin in the top left corner.
+// The side the direction points to is dark and the side it comes from is light.
+pub fn sample_gradient(profile: &GradientProfile, degrees: f32, u: f32, v: f32, seed: u32) -> f32 {
+    let radians = degrees.to_radians();
+    // image y grows downwards, so 90 degrees points up
+    let (dx, dy) = (radians.cos(), -radians.sin());
+    let (px, py) = (u - 0.5, v - 0.5);
+    // half the length of the square projected on the direction, the corners land on 0 and 1
+    let extent = 0.5 * (dx.abs() + dy.abs());
+    let projection = (px * dx + py * dy) / extent;
 
-    // let image_path = "C:/__WORK__/Projects/rust-projects/world/src/image_gradient/0.jpg";
-    let image_path = format!("src/image_gradient/{}.jpg", nr);
-    let path = Path::new(&image_path);
-    let img: DynamicImage = image::open(&path).unwrap();
+    let value = match profile {
+        GradientProfile::Linear => 0.5 - projection / 2.0,
+        GradientProfile::Radial => {
+            // the dome sits on the trailing side of the plate
+            let (cx, cy) = (-dx * 0.25, -dy * 0.25);
+            1.0 - ((px - cx).hypot(py - cy) / 0.75)
+        }
+        GradientProfile::Ridge => 1.0 - projection.abs(),
+        GradientProfile::Perturbed { amplitude, frequency } => {
+            let offset = noise::value(u * frequency, v * frequency, seed, None) * amplitude;
+            0.5 - (projection + offset) / 2.0
+        }
+    };
 
-    img.to_luma8()
-}
\ No newline at end of file
+    value.clamp(0.0, 1.0)
+}
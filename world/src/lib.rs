@@ -1,3 +1,8 @@
+pub mod coloring;
+pub mod contour;
+pub mod font;
 pub mod image_gradient;
+pub mod names;
+pub mod noise;
 
 pub const LIB_NAME: &str = "world";
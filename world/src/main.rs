@@ -1,24 +1,73 @@
+mod adjacency;
+mod climate_builder;
 mod continent_builder;
-mod image_builder;
+mod erosion_builder;
+mod export_builder;
+mod geojson_builder;
+mod heightmap_builder;
+mod hydrology_builder;
+mod naming_builder;
+mod noise_builder;
+mod pipeline;
+mod plate_builder;
+mod politics_builder;
+mod progress;
+mod render_builder;
+mod resource_builder;
+mod road_builder;
+mod settlement_builder;
+mod svg_builder;
+mod tectonics_builder;
+#[cfg(test)]
+mod test_planet;
+mod vector_builder;
 mod voronoi_builder;
 
-use std::{env, time::SystemTime};
+use std::{collections::HashMap, env};
 
 use gamescript::{
     file_read_write,
     models::{
-        continent::{Planet, PlanetSettings, Realm, Region},
+        climate::ClimateSettings,
+        continent::{Continent, MapLevel, Planet, PlanetSettings, Province, Realm, Region},
+        erosion::ErosionSettings,
+        export::ExportSettings,
+        heightmap::HeightmapSettings,
+        hydrology::HydrologySettings,
+        naming::NamingSettings,
+        noise::NoiseSettings,
         point::Size16,
+        politics::PoliticsSettings,
+        render::{self, Attribute, BlendMode, BorderSource, LayerKind, MarkerSource, Palette, RenderLayer, RenderSettings},
+        resource::{Resource, ResourceSettings},
+        road::RoadSettings,
+        settlement::SettlementSettings,
+        tectonics::{PlateBoundary, PlateGenerator, PlateSettings, TectonicSettings},
+        vector::{GeoCoordinates, VectorSettings},
     }, json_read_write, bin_read_write,
 };
+use pipeline::{Pipeline, Stage};
+use progress::Verbosity;
+use render_builder::RenderMap;
 use world::LIB_NAME;
 
+// a fixed seed keeps the stage cache valid between runs, pass --seed for another world
+const DEFAULT_SEED: u64 = 0x5eed;
+
+type Continents = HashMap<(u16, u16), Continent>;
+
 fn main() {
-    let time_now = std::time::SystemTime::now();
     let args: Vec<String> = env::args().collect();
+    if let Some(name) = get_arg_value(&args, "--verbosity") {
+        let verbosity = Verbosity::from_name(name).unwrap_or_else(|| {
+            let names: Vec<&str> = Verbosity::ALL.iter().map(|verbosity| verbosity.name()).collect();
+            panic!("--verbosity expects one of: {}", names.join(", "))
+        });
+        progress::set_verbosity(verbosity);
+    }
     let dir_name: Option<String> = file_read_write::dir_name(LIB_NAME);
     let dist_folder: &str = &format!("{}{}", dir_name.unwrap(), "__dist");
-    println!("dist_folder: {}", dist_folder);
+    progress::log(Verbosity::Verbose, format_args!("dist_folder: {}", dist_folder));
 
     let planet: Planet;
 
@@ -26,28 +75,87 @@ fn main() {
     // let load_and_draw = true;
 
     if load_and_draw {
-        let planet: Planet = bin_read_write::deserialize_bin(&format!("{}\\{}", dist_folder, "planet.bin"));
+        let planet = load_planet(dist_folder);
         let path: &String = &format!("{}\\{}", dist_folder, "planet_settings.json");
         let planet_settings: PlanetSettings = json_read_write::deserialize_json(path);
-        image_builder::build_planet_image(
-            &planet,
-            &planet_settings,
+        let _span = progress::span("draw");
+        render_builder::render_image(
+            &RenderMap::from_planet(&planet_settings, &planet),
+            &[RenderLayer::new(LayerKind::Ramp { attribute: Attribute::Elevation, stops: render::get_grey_ramp() })],
             &format!("{}\\{}", dist_folder, "4__continets.png"),
         );
+    } else if args.contains(&String::from("export-svg")) {
+        let planet = load_planet(dist_folder);
+        let path: &String = &format!("{}\\{}", dist_folder, "planet_settings.json");
+        let planet_settings: PlanetSettings = json_read_write::deserialize_json(path);
+        let _span = progress::span("svg");
+        svg_builder::build_planet_svg(&planet, &planet_settings, &format!("{}\\{}", dist_folder, "7__map.svg"));
+    } else if args.contains(&String::from("export-geojson")) {
+        let planet = load_planet(dist_folder);
+        let path: &String = &format!("{}\\{}", dist_folder, "planet_settings.json");
+        let mut planet_settings: PlanetSettings = json_read_write::deserialize_json(path);
+        if args.contains(&String::from("pixel-coordinates")) {
+            planet_settings.vector_settings.geojson_coordinates = GeoCoordinates::Pixel;
+        }
+        let _span = progress::span("geojson");
+        geojson_builder::build_planet_geojson(&planet, &planet_settings, dist_folder);
+    } else if args.contains(&String::from("export-heightmap")) {
+        let planet = load_planet(dist_folder);
+        let path: &String = &format!("{}\\{}", dist_folder, "planet_settings.json");
+        let planet_settings: PlanetSettings = json_read_write::deserialize_json(path);
+        let _span = progress::span("export");
+        export_builder::export_heightmap(&planet, &planet_settings, dist_folder);
     } else if args.len() == 1
         || args.contains(&String::from("build-and-draw"))
         || args.contains(&String::from("build"))
     {
         // (768, 384);
-        let planet_settings = create_planet_settings(1536, 768, 512, 128, 64, 12);
+        let mut planet_settings = create_planet_settings(1536, 768, 512, 128, 64, 12);
+        if args.contains(&String::from("flood-fill-plates")) {
+            planet_settings.plate_settings.generator = PlateGenerator::FloodFill;
+        }
+        if let Some(seed) = get_arg_value(&args, "--seed") {
+            planet_settings.seed = seed.parse().expect("--seed expects a number");
+        }
+
+        let from = get_arg_value(&args, "--from").map(|name| {
+            Stage::from_name(name).unwrap_or_else(|| {
+                let names: Vec<&str> = Stage::ALL.iter().map(|stage| stage.name()).collect();
+                panic!("--from expects one of: {}", names.join(", "))
+            })
+        });
 
-        planet = build_planet(&planet_settings, dist_folder, time_now);
+        planet = build_planet(&planet_settings, dist_folder, from);
 
         if args.contains(&String::from("build-and-draw")) {
-            image_builder::build_planet_image(
-                &planet, &planet_settings,
+            let _span = progress::span("draw");
+            render_builder::render_image(
+                &RenderMap::from_planet(&planet_settings, &planet),
+                &[RenderLayer::new(LayerKind::Ramp { attribute: Attribute::Elevation, stops: render::get_grey_ramp() })],
                 &format!("{}\\{}", dist_folder, "4__continets.png"),
             );
+            render_builder::render_image(
+                &RenderMap::from_planet(&planet_settings, &planet),
+                &get_atlas_layers(&planet_settings),
+                &format!("{}\\{}", dist_folder, "13__atlas.png"),
+            );
+            drop(_span);
+            let _span = progress::span("svg");
+            svg_builder::build_planet_svg(&planet, &planet_settings, &format!("{}\\{}", dist_folder, "7__map.svg"));
+            drop(_span);
+            let _span = progress::span("geojson");
+            geojson_builder::build_planet_geojson(&planet, &planet_settings, dist_folder);
+            drop(_span);
+            let _span = progress::span("export");
+            export_builder::export_heightmap(&planet, &planet_settings, dist_folder);
+        }
+
+        let report = progress::build_timing_report(planet_settings.seed);
+        progress::log(Verbosity::Normal, format_args!("total {:.1} ms", report.total_ms));
+        // machine readable timings to compare builds between versions
+        if args.contains(&String::from("--timings")) {
+            let path = &format!("{}\\{}", dist_folder, "timings.json");
+            json_read_write::write(&report, path);
         }
     }
 }
@@ -55,112 +163,338 @@ fn main() {
 fn build_planet(
     planet_settings: &PlanetSettings,
     dist_folder: &str,
-    time_now: SystemTime,
+    from: Option<Stage>,
 ) -> Planet {
-    println!("{:?}", planet_settings);
+    progress::log(Verbosity::Debug, format_args!("{:?}", planet_settings));
+    let pipeline = Pipeline::new(planet_settings, dist_folder, from);
 
     // make regions
-
-    let mut regions: Vec<Region>;
-
-    let build_regions = false;
-    if build_regions {
+    let regions: Option<Vec<Region>> = pipeline.run(Stage::Regions, Some(()), |_| {
         let region_sites_len = ((planet_settings.region_grid_size.width / 2)
             * (planet_settings.region_grid_size.height / 2))
             as usize;
-        let sites =
-            voronoi_builder::generate_scattered_sites(&planet_settings.img_size, region_sites_len);
-        regions = continent_builder::build_regions_and_assign_sites(sites);
-        voronoi_builder::build_voronoi_and_apply_site_pixels_and_corners(
-            &planet_settings.img_size,
-            &mut regions,
-        );
-
-        let path = &format!("{}\\{}", dist_folder, "regions.bin");
-        gamescript::bin_read_write::write(&regions, path);
-    } else {
-        regions = gamescript::bin_read_write::deserialize_bin(&format!(
-            "{}\\{}",
-            dist_folder, "regions.bin"
-        ));
-    }
-    println!("Finished regions -> {}", get_elapsed_time(&time_now));
+        let sites = {
+            let mut span = progress::span("sites");
+            span.items(region_sites_len);
+            voronoi_builder::generate_scattered_sites(
+                &planet_settings.img_size,
+                region_sites_len,
+                planet_settings.seed,
+            )
+        };
+        let mut regions = continent_builder::build_regions_and_assign_sites(sites);
+        {
+            let _span = progress::span("voronoi");
+            voronoi_builder::build_voronoi_and_apply_site_pixels_and_corners(
+                &planet_settings.img_size,
+                &mut regions,
+            );
+        }
 
-    image_builder::build_regions_image(
-        &planet_settings.img_size,
-        &regions,
-        &format!("{}\\{}", dist_folder, "1__regions.png"),
-    );
+        let _span = progress::span("image");
+        render_builder::render_image(
+            &RenderMap::from_regions(planet_settings, &regions),
+            &[
+                RenderLayer::new(LayerKind::Fill { level: MapLevel::Region, palette: Palette::Categorical }),
+                planet_settings.render_settings.get_border(MapLevel::Region).get_layer(MapLevel::Region),
+            ],
+            &format!("{}\\{}", dist_folder, "1__regions.png"),
+        );
+        regions
+    });
 
     // make provinces
-    let mut provinces = continent_builder::build_provinces_and_generate_sites(&planet_settings);
-    continent_builder::assign_regions_to_provinces(regions, &mut provinces, &planet_settings);
-    println!("Finished provinces -> {}", get_elapsed_time(&time_now));
-    image_builder::build_provinces_image(
-        &planet_settings.img_size,
-        &provinces,
-        &format!("{}\\{}", dist_folder, "2__provinces.png"),
-    );
+    let provinces: Option<Vec<Province>> = pipeline.run(Stage::Provinces, regions, |regions| {
+        let mut provinces = continent_builder::build_provinces_and_generate_sites(planet_settings);
+        continent_builder::assign_regions_to_provinces(regions, &mut provinces, planet_settings);
+        let _span = progress::span("image");
+        render_builder::render_image(
+            &RenderMap::from_provinces(planet_settings, &provinces),
+            &[
+                RenderLayer::new(LayerKind::Fill { level: MapLevel::Province, palette: Palette::Categorical }),
+                planet_settings.render_settings.get_border(MapLevel::Province).get_layer(MapLevel::Province),
+            ],
+            &format!("{}\\{}", dist_folder, "2__provinces.png"),
+        );
+        provinces
+    });
 
     // make realms
-    let mut realms: Vec<Realm> =
-        continent_builder::build_realms_and_generate_sites(&planet_settings);
-    continent_builder::assign_provinces_to_realms(provinces, &mut realms, &planet_settings);
-    println!("Finished realms -> {}", get_elapsed_time(&time_now));
-    image_builder::build_realms_image(
-        &planet_settings.img_size,
-        &realms,
-        &format!("{}\\{}", dist_folder, "3__realms.png"),
-    );
+    let realms: Option<Vec<Realm>> = pipeline.run(Stage::Realms, provinces, |provinces| {
+        let mut realms = continent_builder::build_realms_and_generate_sites(planet_settings);
+        continent_builder::assign_provinces_to_realms(provinces, &mut realms, planet_settings);
+        let _span = progress::span("image");
+        render_builder::render_image(
+            &RenderMap::from_realms(planet_settings, &realms),
+            &[
+                RenderLayer::new(LayerKind::Fill { level: MapLevel::Realm, palette: Palette::Categorical }),
+                planet_settings.render_settings.get_border(MapLevel::Realm).get_layer(MapLevel::Realm),
+            ],
+            &format!("{}\\{}", dist_folder, "3__realms.png"),
+        );
+        realms
+    });
 
-    // make continents and apply realm to them based off of distance
-    let mut continents = continent_builder::build_continents_with_site(&planet_settings);
-    continent_builder::assign_realms_to_continents_and_calculate_region_color(
-        realms,
-        &mut continents,
-        &planet_settings,
-    );
-    println!(
-        "Finished creating continents -> {}",
-        get_elapsed_time(&time_now)
-    );
+    let continents: Option<Continents> = pipeline.run(Stage::Continents, realms, |realms| {
+        match planet_settings.plate_settings.generator {
+            PlateGenerator::Grid => {
+                // make continents and apply realm to them based off of distance
+                let mut continents = continent_builder::build_continents_with_site(planet_settings);
+                continent_builder::assign_realms_to_continents_and_calculate_region_color(
+                    realms,
+                    &mut continents,
+                    planet_settings,
+                );
+                continents
+            }
+            PlateGenerator::FloodFill => {
+                // grow plates over the realm adjacency graph, they don't need merging
+                plate_builder::build_continents_by_flood_fill(realms, planet_settings)
+            }
+        }
+    });
 
-    //--------
+    let continents: Option<Continents> = pipeline.run(Stage::Merge, continents, |mut continents| {
+        match planet_settings.plate_settings.generator {
+            PlateGenerator::Grid => continent_builder::merge_continents(&mut continents, planet_settings),
+            PlateGenerator::FloodFill => continents,
+        }
+    });
 
-    let mut new_continents
-        = continent_builder::merge_continents(&mut continents, &planet_settings);
-    println!("Merge continents -> {}", get_elapsed_time(&time_now));
+    let elevation: Option<(Continents, Vec<PlateBoundary>)> =
+        pipeline.run(Stage::Elevation, continents, |mut continents| {
+            tectonics_builder::assign_plate_types(&mut continents, planet_settings);
 
+            {
+                let _span = progress::span("image");
+                render_builder::render_image(
+                    &RenderMap::from_continents(planet_settings, &continents),
+                    &[
+                        RenderLayer::new(LayerKind::Fill { level: MapLevel::Continent, palette: Palette::Categorical })
+                            .with_legend(),
+                        planet_settings.render_settings.get_border(MapLevel::Continent).get_layer(MapLevel::Continent),
+                    ],
+                    &format!("{}\\{}", dist_folder, "4__continets.png"),
+                );
+            }
 
-    image_builder::debug_planet_image(
-        &new_continents, &planet_settings,
-        &format!("{}\\{}", dist_folder, "4__continets.png"),
-    );
+            {
+                let _span = progress::span("gradients");
+                continent_builder::assign_continent_gradient_to_pixels(&mut continents, planet_settings);
+            }
+            let mut span = progress::span("boundaries");
+            let plate_boundaries =
+                tectonics_builder::apply_plate_boundaries(&mut continents, planet_settings);
+            span.items(plate_boundaries.len());
+            drop(span);
+            (continents, plate_boundaries)
+        });
+
+    let planet: Option<Planet> =
+        pipeline.run(Stage::Tweaks, elevation, |(mut continents, plate_boundaries)| {
+            {
+                let _span = progress::span("noise");
+                noise_builder::apply_noise_to_regions(&mut continents, planet_settings);
+            }
+            let heightmap = if planet_settings.heightmap_settings.build {
+                let _span = progress::span("heightmap");
+                Some(heightmap_builder::build_heightmap(&continents, planet_settings))
+            } else {
+                None
+            };
+
+            Planet {
+                img_size: Size16 {
+                    width: planet_settings.img_size.width,
+                    height: planet_settings.img_size.height,
+                },
+                continents,
+                plate_boundaries,
+                heightmap,
+                hydrology: None,
+                politics: None,
+                settlements: Vec::new(),
+                roads: Vec::new(),
+            }
+        });
+
+    let planet: Option<Planet> = pipeline.run(Stage::Erosion, planet, |mut planet: Planet| {
+        if planet_settings.erosion_settings.build {
+            let _span = progress::span("droplets");
+            erosion_builder::apply_erosion(&mut planet, planet_settings);
+        }
+        planet
+    });
+
+    let planet: Option<Planet> = pipeline
+        .run(Stage::Hydrology, planet, |mut planet: Planet| {
+            if planet_settings.hydrology_settings.build {
+                let mut span = progress::span("rivers");
+                let hydrology = hydrology_builder::build_hydrology(&planet, planet_settings);
+                span.items(hydrology.rivers.len());
+                planet.hydrology = Some(hydrology);
+            }
+
+            let _span = progress::span("image");
+            let sea_level = planet_settings.tectonic_settings.sea_level;
+            render_builder::render_image(
+                &RenderMap::from_planet(planet_settings, &planet),
+                &[
+                    RenderLayer::new(LayerKind::Ramp {
+                        attribute: Attribute::Elevation,
+                        stops: render::get_sea_and_grey_ramp(sea_level),
+                    }),
+                    RenderLayer::new(LayerKind::Lakes { color: [70, 120, 170] }),
+                    RenderLayer::new(LayerKind::Rivers { color: [60, 110, 200] }),
+                ],
+                &format!("{}\\{}", dist_folder, "5__hydrology.png"),
+            );
+            planet
+        });
+
+    let planet: Option<Planet> = pipeline
+        .run(Stage::Climate, planet, |mut planet: Planet| {
+            if planet_settings.climate_settings.build {
+                let _span = progress::span("biomes");
+                climate_builder::apply_climate(&mut planet, planet_settings);
+            }
+
+            let _span = progress::span("image");
+            render_builder::render_image(
+                &RenderMap::from_planet(planet_settings, &planet),
+                &[RenderLayer::new(LayerKind::Biomes)],
+                &format!("{}\\{}", dist_folder, "6__biomes.png"),
+            );
+            planet
+        });
+
+    let planet: Option<Planet> = pipeline
+        .run(Stage::Resources, planet, |mut planet: Planet| {
+            if planet_settings.resource_settings.build {
+                let _span = progress::span("deposits");
+                resource_builder::apply_resources(&mut planet, planet_settings);
+            }
+
+            let _span = progress::span("image");
+            let map = RenderMap::from_planet(planet_settings, &planet);
+            let sea_level = planet_settings.tectonic_settings.sea_level;
+            for resource in Resource::ALL {
+                render_builder::render_image(
+                    &map,
+                    &[
+                        RenderLayer::new(LayerKind::Ramp {
+                            attribute: Attribute::Elevation,
+                            stops: render::get_dim_ramp(sea_level),
+                        }),
+                        RenderLayer::new(LayerKind::Ramp {
+                            attribute: Attribute::Resource(resource),
+                            stops: render::get_heat_ramp(),
+                        })
+                        .with_legend(),
+                    ],
+                    &format!("{}\\12__resources_{}.png", dist_folder, resource.name()),
+                );
+            }
+            planet
+        });
+
+    let planet: Option<Planet> = pipeline.run(Stage::Names, planet, |mut planet: Planet| {
+        if planet_settings.naming_settings.build {
+            let _span = progress::span("names");
+            naming_builder::apply_names(&mut planet, planet_settings);
+        }
+        planet
+    });
 
+    let planet: Option<Planet> = pipeline
+        .run(Stage::Politics, planet, |mut planet: Planet| {
+            if planet_settings.politics_settings.build {
+                let mut span = progress::span("nations");
+                let politics = politics_builder::build_politics(&planet, planet_settings);
+                span.items(politics.nations.len());
+                planet.politics = Some(politics);
+            }
 
-    //-----------------
+            let _span = progress::span("image");
+            let map = RenderMap::from_planet(planet_settings, &planet);
+            let border_style = &planet_settings.render_settings.nations;
+            let borders: Vec<RenderLayer> =
+                border_style.visible.then(|| border_style.get_layer(BorderSource::Nations)).into_iter().collect();
+            let mut layers: Vec<RenderLayer> = vec![
+                RenderLayer::new(LayerKind::Ramp {
+                    attribute: Attribute::Elevation,
+                    stops: render::get_sea_and_grey_ramp(planet_settings.tectonic_settings.sea_level),
+                }),
+                RenderLayer::new(LayerKind::Nations).with_blend(0.75, BlendMode::Normal),
+            ];
+            layers.extend(borders.iter().cloned());
+            layers.push(RenderLayer::new(LayerKind::Markers { source: MarkerSource::Capitals }));
+            render_builder::render_image(&map, &layers, &format!("{}\\{}", dist_folder, "9__nations.png"));
+            render_builder::render_overlay_image(&map, &borders, &format!("{}\\{}", dist_folder, "9__nation_borders.png"));
+            planet
+        });
 
-    // continent_builder::assign_continent_gradient_to_pixels(&mut new_continents, &planet_settings);
-    // println!("Finished planet -> {}", get_elapsed_time(&time_now));
+    let planet: Option<Planet> = pipeline
+        .run(Stage::Settlements, planet, |mut planet: Planet| {
+            if planet_settings.settlement_settings.build {
+                let mut span = progress::span("settle");
+                planet.settlements = settlement_builder::build_settlements(&planet, planet_settings);
+                span.items(planet.settlements.len());
+            }
+
+            let _span = progress::span("image");
+            render_builder::render_image(
+                &RenderMap::from_planet(planet_settings, &planet),
+                &[
+                    RenderLayer::new(LayerKind::Biomes),
+                    RenderLayer::new(LayerKind::Markers { source: MarkerSource::Settlements }),
+                ],
+                &format!("{}\\{}", dist_folder, "10__settlements.png"),
+            );
+            planet
+        });
+
+    let planet: Planet = pipeline
+        .run(Stage::Roads, planet, |mut planet: Planet| {
+            if planet_settings.road_settings.build {
+                let mut span = progress::span("routes");
+                planet.roads = road_builder::build_roads(&planet, planet_settings);
+                span.items(planet.roads.len());
+            }
+
+            let _span = progress::span("image");
+            render_builder::render_image(
+                &RenderMap::from_planet(planet_settings, &planet),
+                &[
+                    RenderLayer::new(LayerKind::Biomes),
+                    RenderLayer::new(LayerKind::Roads { color: [110, 60, 30], shortcut_color: [170, 120, 70] }),
+                    RenderLayer::new(LayerKind::Markers { source: MarkerSource::Settlements }),
+                ],
+                &format!("{}\\{}", dist_folder, "11__roads.png"),
+            );
+            planet
+        })
+        .unwrap();
 
     // save planet for futher use
-    let planet = Planet {
-        img_size: Size16 {
-            width: planet_settings.img_size.width,
-            height: planet_settings.img_size.height,
-        },
-        continents: new_continents,
-    };
+    let _span = progress::span("save");
     let path = &format!("{}\\{}", dist_folder, "planet.bin");
-    gamescript::bin_read_write::write(&planet, path);
+    gamescript::bin_read_write::write_versioned(&planet, Planet::VERSION, path);
     let path = &format!("{}\\{}", dist_folder, "planet_settings.json");
     gamescript::json_read_write::write(&planet_settings, path);
 
-    println!("{}", get_elapsed_time(&time_now));
-
     planet
 }
 
+// the planet saved by the last build, a planet.bin from another version of the planet asks for a rebuild
+fn load_planet(dist_folder: &str) -> Planet {
+    let path = &format!("{}\\{}", dist_folder, "planet.bin");
+    match bin_read_write::try_deserialize_versioned_bin(path, Planet::VERSION) {
+        Ok(planet) => planet,
+        Err(err) => panic!("{}, run build-and-draw to build the planet again", err),
+    }
+}
+
 fn create_planet_settings(
     width: u16,
     height: u16,
@@ -217,19 +551,47 @@ fn create_planet_settings(
         province_cell_size: province_cell_size,
         realm_cell_size: realm_cell_size,
         continent_cell_size: continent_cell_size,
+        plate_settings: PlateSettings::default(),
+        tectonic_settings: TectonicSettings::default(),
+        seed: DEFAULT_SEED,
+        region_noise: NoiseSettings::default(),
+        pixel_noise: NoiseSettings::detail(),
+        heightmap_settings: HeightmapSettings::default(),
+        erosion_settings: ErosionSettings::default(),
+        hydrology_settings: HydrologySettings::default(),
+        climate_settings: ClimateSettings::default(),
+        vector_settings: VectorSettings::default(),
+        naming_settings: NamingSettings::default(),
+        politics_settings: PoliticsSettings::default(),
+        settlement_settings: SettlementSettings::default(),
+        road_settings: RoadSettings::default(),
+        resource_settings: ResourceSettings::default(),
+        render_settings: RenderSettings::default(),
+        export_settings: ExportSettings::default(),
     }
 }
 
-fn get_elapsed_time(time_now: &SystemTime) -> String {
-    match time_now.elapsed() {
-        Ok(ellapsed) => {
-            return format!(
-                "s: {}, ms: {}, ns: {}",
-                ellapsed.as_secs(),
-                ellapsed.as_millis(),
-                ellapsed.as_nanos()
-            )
-        }
-        Err(_) => return "".to_string(),
-    }
+// relief tinted by realm, the visible borders, settlements, capitals and continent names
+fn get_atlas_layers(planet_settings: &PlanetSettings) -> Vec<RenderLayer> {
+    let render_settings = &planet_settings.render_settings;
+    let sea_level = planet_settings.tectonic_settings.sea_level;
+    let mut layers: Vec<RenderLayer> = vec![
+        RenderLayer::new(LayerKind::Ramp { attribute: Attribute::Elevation, stops: render::get_hypsometric_ramp(sea_level) })
+            .with_legend(),
+        render_settings.relief.get_layer(),
+        RenderLayer::new(LayerKind::Fill { level: MapLevel::Realm, palette: Palette::Categorical })
+            .with_blend(0.25, BlendMode::Multiply),
+    ];
+    layers.extend(render_settings.get_border_layers());
+    layers.extend([
+        RenderLayer::new(LayerKind::Markers { source: MarkerSource::Settlements }),
+        RenderLayer::new(LayerKind::Markers { source: MarkerSource::Capitals }),
+        RenderLayer::new(LayerKind::Labels { level: MapLevel::Continent, color: [255, 255, 255] }),
+    ]);
+    layers
+}
+
+fn get_arg_value<'a>(args: &'a [String], name: &str) -> Option<&'a String> {
+    let index = args.iter().position(|arg| arg == name)?;
+    args.get(index + 1)
 }
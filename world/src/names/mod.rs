@@ -0,0 +1,126 @@
+use std::collections::{HashMap, HashSet};
+
+use rand::Rng;
+
+const START: char = '^';
+const END: char = '$';
+
+// Letter level markov chain trained on a word list. Every state is the last `order` letters,
+// padded with start markers at the beginning of a word.
+pub struct NameGenerator {
+    order: usize,
+    // next letters with their counts, in the order they were first seen so picks stay deterministic
+    transitions: HashMap<String, Vec<(char, u32)>>,
+    words: HashSet<String>,
+}
+
+impl NameGenerator {
+    pub fn new(words: &[String], order: usize) -> NameGenerator {
+        let order = order.max(1);
+        let mut transitions: HashMap<String, Vec<(char, u32)>> = HashMap::new();
+        for word in words {
+            let word = word.trim().to_lowercase();
+            if word.is_empty() {
+                continue;
+            }
+            let letters: Vec<char> = std::iter::repeat_n(START, order).chain(word.chars()).chain([END]).collect();
+            for window in letters.windows(order + 1) {
+                let state: String = window[..order].iter().collect();
+                let next = window[order];
+                let counts = transitions.entry(state).or_default();
+                match counts.iter_mut().find(|(letter, _)| *letter == next) {
+                    Some((_, count)) => *count += 1,
+                    None => counts.push((next, 1)),
+                }
+            }
+        }
+
+        NameGenerator {
+            order,
+            transitions,
+            words: words.iter().map(|word| word.trim().to_lowercase()).collect(),
+        }
+    }
+
+    // A capitalized name of `min_len..=max_len` letters that isn't in the word list itself,
+    // None when the chain didn't come up with one within a few tries.
+    pub fn generate<R: Rng>(&self, rng: &mut R, min_len: usize, max_len: usize) -> Option<String> {
+        for _ in 0..32 {
+            let mut state: Vec<char> = vec![START; self.order];
+            let mut name = String::new();
+            loop {
+                let key: String = state.iter().collect();
+                let next = match self.transitions.get(&key) {
+                    Some(counts) => pick_weighted(counts, rng),
+                    None => END,
+                };
+                if next == END || name.chars().count() > max_len {
+                    break;
+                }
+                name.push(next);
+                state.remove(0);
+                state.push(next);
+            }
+
+            let len = name.chars().count();
+            if len >= min_len && len <= max_len && !self.words.contains(&name) {
+                return Some(capitalize(&name));
+            }
+        }
+        None
+    }
+}
+
+fn pick_weighted<R: Rng>(counts: &[(char, u32)], rng: &mut R) -> char {
+    let total: u32 = counts.iter().map(|(_, count)| count).sum();
+    let mut pick = rng.gen_range(0..total);
+    for (letter, count) in counts {
+        if pick < *count {
+            return *letter;
+        }
+        pick -= count;
+    }
+    END
+}
+
+// upper case at the start and after spaces, hyphens and apostrophes
+fn capitalize(name: &str) -> String {
+    let mut capitalized = String::with_capacity(name.len());
+    let mut upper = true;
+    for letter in name.chars() {
+        if upper {
+            capitalized.extend(letter.to_uppercase());
+        } else {
+            capitalized.push(letter);
+        }
+        upper = matches!(letter, ' ' | '-' | '\'');
+    }
+    capitalized
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::{rngs::StdRng, SeedableRng};
+
+    #[test]
+    fn same_seed_generates_the_same_names() {
+        let words: Vec<String> = ["valeria", "verona", "ravenna", "ancona", "capua", "mantua", "ostia", "sabina"]
+            .iter()
+            .map(|word| word.to_string())
+            .collect();
+        let generator = NameGenerator::new(&words, 2);
+        let generate = |seed: u64| -> Vec<Option<String>> {
+            let mut rng = StdRng::seed_from_u64(seed);
+            (0..8).map(|_| generator.generate(&mut rng, 4, 9)).collect()
+        };
+
+        let names = generate(7);
+        assert_eq!(names, generate(7));
+        for name in names.iter().flatten() {
+            assert!((4..=9).contains(&name.chars().count()), "{}", name);
+            assert!(name.chars().next().unwrap().is_uppercase(), "{}", name);
+            assert!(!words.contains(&name.to_lowercase()), "{}", name);
+        }
+    }
+}
@@ -0,0 +1,79 @@
+use std::collections::HashSet;
+
+use gamescript::models::{
+    continent::{Planet, PlanetSettings},
+    naming::NamingSettings,
+};
+use rand::{rngs::StdRng, SeedableRng};
+use world::names::NameGenerator;
+
+use crate::adjacency;
+
+pub const NAMING_SALT: u64 = 0x5eed_0004;
+
+// Names every continent, realm and province. Each continent picks a style from a hash of
+// its place in the walk and names are drawn from one seeded rng in that walk. Continent
+// names are unique on the planet, realm and province names within their continent.
+pub fn apply_names(planet: &mut Planet, planet_settings: &PlanetSettings) {
+    let settings = &planet_settings.naming_settings;
+    if settings.styles.is_empty() {
+        return;
+    }
+    let generators = build_generators(settings);
+
+    let mut rng = StdRng::seed_from_u64(planet_settings.seed ^ NAMING_SALT);
+    let mut continent_names: HashSet<String> = HashSet::new();
+    let keys = adjacency::get_sorted_continent_keys(&planet.continents);
+    for (i, key) in keys.iter().enumerate() {
+        let hash = world::noise::hash(i as i32, 0, (planet_settings.seed ^ NAMING_SALT) as u32);
+        let style = ((hash * settings.styles.len() as f32) as usize).min(settings.styles.len() - 1);
+        let generator = &generators[style];
+
+        let continent = planet.continents.get_mut(key).unwrap();
+        continent.name_style = settings.styles[style].name.clone();
+        continent.name = get_unique_name(generator, settings, &mut rng, &mut continent_names);
+        let mut used: HashSet<String> = HashSet::from([continent.name.clone()]);
+        for rlm in &mut continent.realms {
+            rlm.name = get_unique_name(generator, settings, &mut rng, &mut used);
+            for pv in &mut rlm.provinces {
+                pv.name = get_unique_name(generator, settings, &mut rng, &mut used);
+            }
+        }
+    }
+}
+
+// one generator per style, in the order of the settings
+pub fn build_generators(settings: &NamingSettings) -> Vec<NameGenerator> {
+    settings
+        .styles
+        .iter()
+        .map(|style| NameGenerator::new(&style.words, settings.order as usize))
+        .collect()
+}
+
+// falls back to numbering a name when the chain keeps repeating itself
+pub fn get_unique_name(
+    generator: &NameGenerator,
+    settings: &NamingSettings,
+    rng: &mut StdRng,
+    used: &mut HashSet<String>,
+) -> String {
+    let (min_len, max_len) = (settings.min_length as usize, settings.max_length.max(settings.min_length) as usize);
+    let mut last: Option<String> = None;
+    for _ in 0..16 {
+        if let Some(name) = generator.generate(rng, min_len, max_len) {
+            if used.insert(name.clone()) {
+                return name;
+            }
+            last = Some(name);
+        }
+    }
+
+    let base = last.unwrap_or_else(|| String::from("Unnamed"));
+    let name = (2..)
+        .map(|number| format!("{} {}", base, number))
+        .find(|name| !used.contains(name))
+        .unwrap();
+    used.insert(name.clone());
+    name
+}
@@ -0,0 +1,234 @@
+use gamescript::models::noise::{NoiseKind, NoiseSettings};
+use std::f32::consts::{FRAC_1_SQRT_2, FRAC_PI_2};
+
+const WARP_X_SEED: u32 = 0x68e3_1da4;
+const WARP_Y_SEED: u32 = 0xb529_7a4d;
+const GRADIENTS: [(f32, f32); 8] = [
+    (1.0, 0.0), (-1.0, 0.0), (0.0, 1.0), (0.0, -1.0),
+    (FRAC_1_SQRT_2, FRAC_1_SQRT_2), (-FRAC_1_SQRT_2, FRAC_1_SQRT_2),
+    (FRAC_1_SQRT_2, -FRAC_1_SQRT_2), (-FRAC_1_SQRT_2, -FRAC_1_SQRT_2),
+];
+
+// Seedable fractal noise sampled in pixel coordinates, returns values in -1..1.
+// With `wrap_x` every octave snaps to a whole number of cycles over the map width,
+// so the left and right edges of a cylindrical map line up.
+pub struct Noise {
+    seed: u32,
+    settings: NoiseSettings,
+    width: f32,
+}
+
+impl Noise {
+    pub fn new(seed: u32, settings: &NoiseSettings, width: u16) -> Noise {
+        Noise {
+            seed,
+            settings: *settings,
+            width: width.max(1) as f32,
+        }
+    }
+
+    pub fn sample(&self, x: f32, y: f32) -> f32 {
+        if self.settings.warp_strength <= 0.0 {
+            return self.fbm(x, y, self.settings.frequency, self.settings.octaves, self.seed);
+        }
+
+        let warp_octaves = self.settings.octaves.min(3);
+        let warp_x = self.fbm(x, y, self.settings.warp_frequency, warp_octaves, self.seed ^ WARP_X_SEED);
+        let warp_y = self.fbm(x, y, self.settings.warp_frequency, warp_octaves, self.seed ^ WARP_Y_SEED);
+        self.fbm(
+            x + warp_x * self.settings.warp_strength,
+            y + warp_y * self.settings.warp_strength,
+            self.settings.frequency,
+            self.settings.octaves,
+            self.seed,
+        )
+    }
+
+    fn fbm(&self, x: f32, y: f32, frequency: f32, octaves: u8, seed: u32) -> f32 {
+        let mut sum = 0.0;
+        let mut amplitude = 1.0;
+        let mut amplitude_sum = 0.0;
+        let mut cycles = frequency;
+
+        for octave in 0..octaves.max(1) as u32 {
+            let octave_seed = seed.wrapping_add(octave.wrapping_mul(0x9e37_79b9));
+            sum += amplitude * self.octave(x, y, cycles, octave_seed);
+            amplitude_sum += amplitude;
+            amplitude *= self.settings.gain;
+            cycles *= self.settings.lacunarity;
+        }
+
+        sum / amplitude_sum
+    }
+
+    fn octave(&self, x: f32, y: f32, cycles: f32, seed: u32) -> f32 {
+        if !self.settings.wrap_x {
+            let scale = cycles / self.width;
+            return sample_kind(self.settings.kind, x * scale, y * scale, seed, None);
+        }
+
+        let period = cycles.round().max(1.0);
+        let scale = period / self.width;
+        let x = x.rem_euclid(self.width);
+        match self.settings.kind {
+            NoiseKind::Value | NoiseKind::Perlin => {
+                sample_kind(self.settings.kind, x * scale, y * scale, seed, Some(period as i32))
+            }
+            // the simplex lattice is skewed, so its seam is blended instead of tiled: over the last
+            // lattice cell the noise fades into its copy one map width to the west. Both samples are
+            // independent, cos and sin weights keep the variance of a single one.
+            NoiseKind::Simplex => {
+                let here = simplex(x * scale, y * scale, seed);
+                let band = self.width / period;
+                let t = (x - (self.width - band)) / band;
+                if t <= 0.0 {
+                    return here;
+                }
+                let wrapped = simplex((x - self.width) * scale, y * scale, seed);
+                let angle = t * FRAC_PI_2;
+                (here * angle.cos() + wrapped * angle.sin()).clamp(-1.0, 1.0)
+            }
+        }
+    }
+}
+
+fn sample_kind(kind: NoiseKind, x: f32, y: f32, seed: u32, period: Option<i32>) -> f32 {
+    match kind {
+        NoiseKind::Value => value(x, y, seed, period),
+        NoiseKind::Perlin => perlin(x, y, seed, period),
+        NoiseKind::Simplex => simplex(x, y, seed),
+    }
+}
+
+// smooth value noise on an integer lattice, x repeats every `period` cells when given
+pub fn value(x: f32, y: f32, seed: u32, period: Option<i32>) -> f32 {
+    let (x0, y0) = (x.floor(), y.floor());
+    let (tx, ty) = (fade(x - x0), fade(y - y0));
+    let (ix, iy) = (x0 as i32, y0 as i32);
+    let (ix0, ix1) = (wrap(ix, period), wrap(ix + 1, period));
+
+    let top = lerp(hash(ix0, iy, seed), hash(ix1, iy, seed), tx);
+    let bottom = lerp(hash(ix0, iy + 1, seed), hash(ix1, iy + 1, seed), tx);
+    lerp(top, bottom, ty) * 2.0 - 1.0
+}
+
+pub fn perlin(x: f32, y: f32, seed: u32, period: Option<i32>) -> f32 {
+    let (x0, y0) = (x.floor(), y.floor());
+    let (fx, fy) = (x - x0, y - y0);
+    let (ix, iy) = (x0 as i32, y0 as i32);
+    let (ix0, ix1) = (wrap(ix, period), wrap(ix + 1, period));
+
+    let dot = |cx: i32, cy: i32, dx: f32, dy: f32| -> f32 {
+        let gradient = GRADIENTS[(hash_u32(cx, cy, seed) & 7) as usize];
+        gradient.0 * dx + gradient.1 * dy
+    };
+
+    let (tx, ty) = (fade(fx), fade(fy));
+    let top = lerp(dot(ix0, iy, fx, fy), dot(ix1, iy, fx - 1.0, fy), tx);
+    let bottom = lerp(dot(ix0, iy + 1, fx, fy - 1.0), dot(ix1, iy + 1, fx - 1.0, fy - 1.0), tx);
+    // 2d perlin stays within about +-0.71
+    (lerp(top, bottom, ty) * std::f32::consts::SQRT_2).clamp(-1.0, 1.0)
+}
+
+pub fn simplex(x: f32, y: f32, seed: u32) -> f32 {
+    const F2: f32 = 0.366_025_42; // (sqrt(3) - 1) / 2
+    const G2: f32 = 0.211_324_87; // (3 - sqrt(3)) / 6
+
+    let s = (x + y) * F2;
+    let (i, j) = ((x + s).floor(), (y + s).floor());
+    let t = (i + j) * G2;
+    let (x0, y0) = (x - (i - t), y - (j - t));
+
+    let (i1, j1) = if x0 > y0 { (1, 0) } else { (0, 1) };
+    let (x1, y1) = (x0 - i1 as f32 + G2, y0 - j1 as f32 + G2);
+    let (x2, y2) = (x0 - 1.0 + 2.0 * G2, y0 - 1.0 + 2.0 * G2);
+    let (i, j) = (i as i32, j as i32);
+
+    let corner = |cx: i32, cy: i32, dx: f32, dy: f32| -> f32 {
+        let falloff = 0.5 - dx * dx - dy * dy;
+        if falloff <= 0.0 {
+            return 0.0;
+        }
+        let gradient = GRADIENTS[(hash_u32(cx, cy, seed) & 7) as usize];
+        falloff.powi(4) * (gradient.0 * dx + gradient.1 * dy)
+    };
+
+    let n = corner(i, j, x0, y0) + corner(i + i1, j + j1, x1, y1) + corner(i + 1, j + 1, x2, y2);
+    (n * 70.0).clamp(-1.0, 1.0)
+}
+
+// lattice hash in 0..1
+pub fn hash(x: i32, y: i32, seed: u32) -> f32 {
+    hash_u32(x, y, seed) as f32 / u32::MAX as f32
+}
+
+fn hash_u32(x: i32, y: i32, seed: u32) -> u32 {
+    let mut h = (x as u32).wrapping_mul(0x27d4_eb2d) ^ (y as u32).wrapping_mul(0x1656_67b1) ^ seed;
+    h = (h ^ (h >> 15)).wrapping_mul(0x85eb_ca6b);
+    h = (h ^ (h >> 13)).wrapping_mul(0xc2b2_ae35);
+    h ^ (h >> 16)
+}
+
+fn wrap(i: i32, period: Option<i32>) -> i32 {
+    match period {
+        Some(period) => i.rem_euclid(period),
+        None => i,
+    }
+}
+
+fn fade(t: f32) -> f32 {
+    t * t * t * (t * (t * 6.0 - 15.0) + 10.0)
+}
+
+fn lerp(a: f32, b: f32, t: f32) -> f32 {
+    a + (b - a) * t
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn same_seed_gives_same_noise() {
+        let settings = NoiseSettings::default();
+        let a = Noise::new(7, &settings, 512);
+        let b = Noise::new(7, &settings, 512);
+        let c = Noise::new(8, &settings, 512);
+
+        assert_eq!(a.sample(123.0, 45.0), b.sample(123.0, 45.0));
+        assert_ne!(a.sample(123.0, 45.0), c.sample(123.0, 45.0));
+    }
+
+    #[test]
+    fn wrapped_noise_meets_at_the_edges() {
+        for kind in [NoiseKind::Value, NoiseKind::Perlin, NoiseKind::Simplex] {
+            let settings = NoiseSettings { kind, frequency: 5.3, ..NoiseSettings::default() };
+            let noise = Noise::new(3, &settings, 512);
+            for y in [0.0, 100.5, 300.0] {
+                let left = noise.sample(0.0, y);
+                let right = noise.sample(511.999, y);
+                assert!((left - right).abs() < 0.01, "{:?} {} {}", kind, left, right);
+            }
+        }
+    }
+
+    #[test]
+    fn wrapped_simplex_keeps_its_variance_across_the_map() {
+        let settings = NoiseSettings { kind: NoiseKind::Simplex, octaves: 1, warp_strength: 0.0, ..NoiseSettings::default() };
+        let noise = Noise::new(11, &settings, 512);
+
+        // variance of eight vertical strips, the last one holds the seam band
+        let variances: Vec<f32> = (0..8)
+            .map(|strip| {
+                let samples: Vec<f32> = (0..64)
+                    .flat_map(|x| (0..256).map(move |y| (strip as f32 * 64.0 + x as f32, y as f32 * 3.7)))
+                    .map(|(x, y)| noise.sample(x, y))
+                    .collect();
+                let mean = samples.iter().sum::<f32>() / samples.len() as f32;
+                samples.iter().map(|v| (v - mean).powi(2)).sum::<f32>() / samples.len() as f32
+            })
+            .collect();
+        let (min, max) = variances.iter().fold((f32::MAX, 0.0_f32), |(min, max), &v| (min.min(v), max.max(v)));
+        assert!(max / min < 1.25, "{:?}", variances);
+    }
+}
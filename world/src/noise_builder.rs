@@ -0,0 +1,62 @@
+use gamescript::models::{
+    continent::{Continent, PlanetSettings},
+    point::{denormalize_u8, normalize_u8},
+};
+use std::collections::HashMap;
+use world::noise::Noise;
+
+use crate::continent_builder;
+
+pub const REGION_NOISE_SALT: u64 = 0x5eed_0001;
+pub const PIXEL_NOISE_SALT: u64 = 0x5eed_0002;
+
+pub fn get_noise_seed(planet_settings: &PlanetSettings, salt: u64) -> u32 {
+    let seed = planet_settings.seed ^ salt.wrapping_mul(0x9e37_79b9_7f4a_7c15);
+    (seed ^ (seed >> 32)) as u32
+}
+
+pub fn build_region_noise(planet_settings: &PlanetSettings) -> Noise {
+    Noise::new(
+        get_noise_seed(planet_settings, REGION_NOISE_SALT),
+        &planet_settings.region_noise,
+        planet_settings.img_size.width,
+    )
+}
+
+pub fn build_pixel_noise(planet_settings: &PlanetSettings) -> Noise {
+    Noise::new(
+        get_noise_seed(planet_settings, PIXEL_NOISE_SALT),
+        &planet_settings.pixel_noise,
+        planet_settings.img_size.width,
+    )
+}
+
+// adds low frequency relief to every region, sampled at the region site
+pub fn apply_noise_to_regions(
+    continents: &mut HashMap<(u16, u16), Continent>,
+    planet_settings: &PlanetSettings,
+) {
+    let noise = build_region_noise(planet_settings);
+    let weight = planet_settings.region_noise.weight as f64;
+
+    for continent in continents.values_mut() {
+        for rlm in &mut continent.realms {
+            for pv in &mut rlm.provinces {
+                for rg in &mut pv.regions {
+                    let value = noise.sample(rg.site_point.x as f32, rg.site_point.y as f32) as f64;
+                    let new_value = normalize_u8(rg.grey_value as f64) + value * weight;
+                    rg.grey_value = denormalize_u8(new_value.clamp(0.0, 1.0));
+                }
+            }
+        }
+    }
+
+    continent_builder::calculate_average_grey_values(continents);
+}
+
+// grey value of a single pixel with the fine detail noise on top of its region value
+pub fn get_pixel_grey_value(noise: &Noise, planet_settings: &PlanetSettings, grey_value: u8, x: u16, y: u16) -> u8 {
+    let value = noise.sample(x as f32, y as f32) as f64;
+    let new_value = normalize_u8(grey_value as f64) + value * planet_settings.pixel_noise.weight as f64;
+    denormalize_u8(new_value.clamp(0.0, 1.0))
+}
@@ -0,0 +1,255 @@
+use std::collections::HashMap;
+
+use gamescript::{
+    bin_read_write,
+    models::{
+        continent::{Planet, PlanetSettings},
+        tectonics::PlateBoundary,
+    },
+};
+use serde::{de::DeserializeOwned, Serialize};
+
+use crate::progress;
+
+#[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Debug)]
+pub enum Stage {
+    Regions,
+    Provinces,
+    Realms,
+    Continents,
+    Merge,
+    Elevation,
+    Tweaks,
+    Erosion,
+    Hydrology,
+    Climate,
+    Resources,
+    Names,
+    Politics,
+    Settlements,
+    Roads,
+}
+
+impl Stage {
+    pub const ALL: [Stage; 15] = [
+        Stage::Regions,
+        Stage::Provinces,
+        Stage::Realms,
+        Stage::Continents,
+        Stage::Merge,
+        Stage::Elevation,
+        Stage::Tweaks,
+        Stage::Erosion,
+        Stage::Hydrology,
+        Stage::Climate,
+        Stage::Resources,
+        Stage::Names,
+        Stage::Politics,
+        Stage::Settlements,
+        Stage::Roads,
+    ];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Stage::Regions => "regions",
+            Stage::Provinces => "provinces",
+            Stage::Realms => "realms",
+            Stage::Continents => "continents",
+            Stage::Merge => "merge",
+            Stage::Elevation => "elevation",
+            Stage::Tweaks => "tweaks",
+            Stage::Erosion => "erosion",
+            Stage::Hydrology => "hydrology",
+            Stage::Climate => "climate",
+            Stage::Resources => "resources",
+            Stage::Names => "names",
+            Stage::Politics => "politics",
+            Stage::Settlements => "settlements",
+            Stage::Roads => "roads",
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<Stage> {
+        Stage::ALL.iter().copied().find(|stage| stage.name() == name)
+    }
+}
+
+// Runs the world build stage by stage. Every stage output is cached under a hash of the
+// previous stage hash and the settings the stage reads, so a changed setting only rebuilds
+// the stages that depend on it. Stages before the first one to rebuild are loaded from the
+// cache only when the next stage needs their output.
+pub struct Pipeline {
+    cache_folder: String,
+    hashes: Vec<u64>,
+    first_dirty: Option<Stage>,
+}
+
+impl Pipeline {
+    pub fn new(planet_settings: &PlanetSettings, dist_folder: &str, from: Option<Stage>) -> Pipeline {
+        let cache_folder = format!("{}\\{}", dist_folder, "cache");
+        if let Err(err) = std::fs::create_dir_all(&cache_folder) {
+            eprintln!("Error creating the cache folder: {}", err);
+        }
+
+        let mut hashes: Vec<u64> = Vec::with_capacity(Stage::ALL.len());
+        let mut hash: u64 = fnv1a(FNV_OFFSET_BASIS, &CACHE_VERSION.to_le_bytes());
+        for stage in Stage::ALL {
+            hash = fnv1a(hash, stage.name().as_bytes());
+            hash = fnv1a(hash, &get_stage_settings_bytes(stage, planet_settings));
+            hashes.push(hash);
+        }
+
+        let mut pipeline = Pipeline { cache_folder, hashes, first_dirty: None };
+        let first_missing = Stage::ALL
+            .iter()
+            .copied()
+            .find(|&stage| !std::path::Path::new(&pipeline.get_cache_path(stage)).exists());
+        pipeline.first_dirty = match (from, first_missing) {
+            (Some(from), Some(missing)) => Some(from.min(missing)),
+            (from, missing) => from.or(missing),
+        };
+        pipeline
+    }
+
+    pub fn is_built(&self, stage: Stage) -> bool {
+        self.first_dirty.is_some_and(|first_dirty| stage >= first_dirty)
+    }
+
+    // Returns the stage output, built from `input` or loaded from the cache. None when the
+    // stage is cached and no later stage has to be rebuilt from it.
+    pub fn run<I, O, F>(&self, stage: Stage, input: Option<I>, build: F) -> Option<O>
+    where
+        O: Serialize + DeserializeOwned + StageOutput,
+        F: FnOnce(I) -> O,
+    {
+        let path = self.get_cache_path(stage);
+        let mut span = progress::span(stage.name());
+
+        if !self.is_built(stage) {
+            if !self.is_needed(stage) {
+                span.note("cached");
+                return None;
+            }
+            if let Some(output) = bin_read_write::try_deserialize_bin::<O>(&path) {
+                span.items(output.items());
+                span.note("loaded from cache");
+                return Some(output);
+            }
+            panic!("Could not load the cached {} stage, rerun with --from {}", stage.name(), stage.name());
+        }
+
+        let input = input.unwrap_or_else(|| panic!("{} stage is missing its input", stage.name()));
+        let output = build(input);
+        span.items(output.items());
+        bin_read_write::write(&output, &path);
+        Some(output)
+    }
+
+    // a cached stage is needed when the next one rebuilds, the last stage is always needed
+    fn is_needed(&self, stage: Stage) -> bool {
+        let next = Stage::ALL.iter().copied().find(|&next| next > stage);
+        match next {
+            Some(next) => self.is_built(next),
+            None => true,
+        }
+    }
+
+    fn get_cache_path(&self, stage: Stage) -> String {
+        format!(
+            "{}\\{}_{:016x}.bin",
+            self.cache_folder, stage.name(), self.hashes[stage as usize]
+        )
+    }
+}
+
+// number of items a stage produced, reported with its timing
+pub trait StageOutput {
+    fn items(&self) -> usize;
+}
+
+impl<T> StageOutput for Vec<T> {
+    fn items(&self) -> usize {
+        self.len()
+    }
+}
+
+impl<K, V> StageOutput for HashMap<K, V> {
+    fn items(&self) -> usize {
+        self.len()
+    }
+}
+
+impl<K, V> StageOutput for (HashMap<K, V>, Vec<PlateBoundary>) {
+    fn items(&self) -> usize {
+        self.0.len()
+    }
+}
+
+impl StageOutput for Planet {
+    fn items(&self) -> usize {
+        self.continents.len()
+    }
+}
+
+// settings a stage reads, the border styles of the stage images drawn inside a stage included
+fn get_stage_settings_bytes(stage: Stage, planet_settings: &PlanetSettings) -> Vec<u8> {
+    let ps = planet_settings;
+    let borders = &ps.render_settings;
+    match stage {
+        Stage::Regions => {
+            bin_read_write::serialize_bin(&(&ps.img_size, &ps.region_grid_size, ps.seed, &borders.regions))
+        }
+        Stage::Provinces => bin_read_write::serialize_bin(&(
+            &ps.province_grid_size,
+            &ps.province_cell_size,
+            ps.seed,
+            &borders.provinces,
+        )),
+        Stage::Realms => {
+            bin_read_write::serialize_bin(&(&ps.realm_grid_size, &ps.realm_cell_size, ps.seed, &borders.realms))
+        }
+        Stage::Continents => bin_read_write::serialize_bin(&(
+            &ps.continent_grid_size,
+            &ps.continent_cell_size,
+            ps.plate_settings.generator,
+            ps.plate_settings.plate_count,
+            ps.plate_settings.growth_rate_min,
+            ps.plate_settings.growth_rate_max,
+            ps.seed,
+        )),
+        Stage::Merge => Vec::new(),
+        Stage::Elevation => bin_read_write::serialize_bin(&(
+            &ps.plate_settings,
+            &ps.tectonic_settings,
+            ps.seed,
+            &borders.continents,
+        )),
+        Stage::Tweaks => bin_read_write::serialize_bin(&(
+            &ps.region_noise,
+            &ps.pixel_noise,
+            &ps.heightmap_settings,
+            ps.seed,
+        )),
+        Stage::Erosion => bin_read_write::serialize_bin(&ps.erosion_settings),
+        Stage::Hydrology => bin_read_write::serialize_bin(&ps.hydrology_settings),
+        Stage::Climate => bin_read_write::serialize_bin(&ps.climate_settings),
+        Stage::Resources => bin_read_write::serialize_bin(&(&ps.resource_settings, ps.seed)),
+        Stage::Names => bin_read_write::serialize_bin(&(&ps.naming_settings, ps.seed)),
+        Stage::Politics => bin_read_write::serialize_bin(&(&ps.politics_settings, &borders.nations)),
+        Stage::Settlements => bin_read_write::serialize_bin(&ps.settlement_settings),
+        Stage::Roads => bin_read_write::serialize_bin(&ps.road_settings),
+    }
+}
+
+// bump when a cached type changes, so old cache files are not read back
+const CACHE_VERSION: u32 = 8;
+const FNV_OFFSET_BASIS: u64 = 0xcbf2_9ce4_8422_2325;
+const FNV_PRIME: u64 = 0x0000_0100_0000_01b3;
+
+fn fnv1a(mut hash: u64, bytes: &[u8]) -> u64 {
+    for byte in bytes {
+        hash ^= *byte as u64;
+        hash = hash.wrapping_mul(FNV_PRIME);
+    }
+    hash
+}
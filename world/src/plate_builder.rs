@@ -0,0 +1,201 @@
+use gamescript::models::{
+    continent::{Continent, PlanetSettings, Realm},
+    point::{calculate_distance, try_map_points_min_max_points_by_points, Point16},
+};
+use rand::{rngs::StdRng, Rng, SeedableRng};
+use std::collections::HashMap;
+use world::image_gradient;
+
+use crate::{adjacency, progress::{self, Verbosity}};
+
+pub const PLATE_SALT: u64 = 0x5eed_000c;
+
+const UNASSIGNED: usize = usize::MAX;
+const SEED_PLACEMENT_ATTEMPTS: u16 = 64;
+
+struct Plate {
+    seed_realm: usize,
+    growth_rate: f32,
+    frontier: Vec<usize>,
+}
+
+// Seeds K plates on random realms and grows them across the realm adjacency graph.
+// Every step picks a plate weighted by its growth rate and claims a random realm of
+// its frontier, so plates come out in irregular shapes and uneven sizes.
+pub fn build_continents_by_flood_fill(
+    realms: Vec<Realm>,
+    planet_settings: &PlanetSettings,
+) -> HashMap<(u16, u16), Continent> {
+    let owners = adjacency::build_realms_owner_raster(&planet_settings.img_size, &realms);
+    let adjacency = adjacency::build_adjacency(&planet_settings.img_size, &owners, realms.len(), true);
+
+    let realms_with_pixels = realms.iter().filter(|rlm| !rlm.provinces.is_empty()).count();
+    let plate_count = get_plate_count(planet_settings).min(realms_with_pixels);
+    // no realm to seed a plate on, so there is nothing to grow
+    if plate_count == 0 {
+        progress::log(Verbosity::Normal, format_args!("no realm has provinces, the planet gets no plates"));
+        return HashMap::new();
+    }
+    let mut rng = StdRng::seed_from_u64(planet_settings.seed ^ PLATE_SALT);
+    let mut plates = seed_plates(&realms, plate_count, planet_settings, &mut rng);
+
+    let mut realm_plates: Vec<usize> = vec![UNASSIGNED; realms.len()];
+    for (p, plate) in plates.iter_mut().enumerate() {
+        realm_plates[plate.seed_realm] = p;
+        plate.frontier.extend(&adjacency[plate.seed_realm]);
+    }
+
+    grow_plates(&mut plates, &mut realm_plates, &adjacency, &mut rng);
+
+    // realms without pixels have no neighbours, they go to the nearest plate seed
+    for (i, rlm) in realms.iter().enumerate() {
+        if realm_plates[i] == UNASSIGNED {
+            realm_plates[i] = get_nearest_plate(&rlm.site_point, &plates, &realms);
+        }
+    }
+
+    let mut continents: Vec<Continent> = plates
+        .iter()
+        .enumerate()
+        .map(|(p, plate)| {
+            Continent::new(
+                get_plate_grid_coord(p, planet_settings),
+                Point16::new(
+                    realms[plate.seed_realm].site_point.x,
+                    realms[plate.seed_realm].site_point.y,
+                ),
+                image_gradient::get_random_degrees_index(&mut rng),
+                // elevation depends on the plate type, which is known once realms are assigned
+                0.0,
+            )
+        })
+        .collect();
+
+    for (i, rlm) in realms.into_iter().enumerate() {
+        let continent = &mut continents[realm_plates[i]];
+
+        // assign new corners if we found new min or max
+        try_map_points_min_max_points_by_points(
+            &mut continent.bottom_left,
+            &mut continent.top_right,
+            &rlm.bottom_left,
+            &rlm.top_right,
+        );
+
+        continent.realms.push(rlm);
+    }
+
+    continents
+        .into_iter()
+        .map(|continent| ((continent.grid_coord.x, continent.grid_coord.y), continent))
+        .collect()
+}
+
+// at most one plate per continent grid cell, so every plate gets a key inside the grid
+fn get_plate_count(planet_settings: &PlanetSettings) -> usize {
+    let grid_cells =
+        planet_settings.continent_grid_size.width as usize * planet_settings.continent_grid_size.height as usize;
+    match planet_settings.plate_settings.plate_count {
+        0 => grid_cells,
+        plate_count => (plate_count as usize).min(grid_cells),
+    }
+}
+
+// plates are keyed row by row over the continent grid width, so consumers
+// iterating the continent grid still find every plate
+fn get_plate_grid_coord(plate_index: usize, planet_settings: &PlanetSettings) -> Point16 {
+    let grid_width = planet_settings.continent_grid_size.width as usize;
+    Point16::new((plate_index % grid_width) as u16, (plate_index / grid_width) as u16)
+}
+
+fn seed_plates(realms: &[Realm], plate_count: usize, planet_settings: &PlanetSettings, rng: &mut StdRng) -> Vec<Plate> {
+    let plate_settings = &planet_settings.plate_settings;
+    // try to keep seeds half a continent cell apart, give up after a few attempts
+    let min_distance = planet_settings.continent_cell_size.width as f32 / 2.0;
+
+    let mut plates: Vec<Plate> = Vec::with_capacity(plate_count);
+    let mut is_seed: Vec<bool> = vec![false; realms.len()];
+
+    while plates.len() < plate_count {
+        let mut seed_realm = rng.gen_range(0..realms.len());
+        for _ in 0..SEED_PLACEMENT_ATTEMPTS {
+            let is_far_enough = plates.iter().all(|plate| {
+                calculate_distance(&realms[seed_realm].site_point, &realms[plate.seed_realm].site_point)
+                    >= min_distance
+            });
+            if !is_seed[seed_realm] && !realms[seed_realm].provinces.is_empty() && is_far_enough {
+                break;
+            }
+            seed_realm = rng.gen_range(0..realms.len());
+        }
+        // realms without provinces have no pixels to grow from
+        if is_seed[seed_realm] || realms[seed_realm].provinces.is_empty() {
+            continue;
+        }
+
+        is_seed[seed_realm] = true;
+        plates.push(Plate {
+            seed_realm,
+            growth_rate: rng.gen_range(
+                plate_settings.growth_rate_min..=plate_settings.growth_rate_max.max(plate_settings.growth_rate_min),
+            ),
+            frontier: Vec::new(),
+        });
+    }
+
+    plates
+}
+
+fn grow_plates(plates: &mut [Plate], realm_plates: &mut [usize], adjacency: &[Vec<usize>], rng: &mut StdRng) {
+
+    loop {
+        let total_rate: f32 = plates
+            .iter()
+            .filter(|plate| !plate.frontier.is_empty())
+            .map(|plate| plate.growth_rate)
+            .sum();
+        if total_rate <= 0.0 {
+            break;
+        }
+
+        // roulette pick of a growing plate
+        let mut pick = rng.gen_range(0.0..total_rate);
+        let mut p = 0;
+        for (i, plate) in plates.iter().enumerate() {
+            if plate.frontier.is_empty() {
+                continue;
+            }
+            p = i;
+            if pick < plate.growth_rate {
+                break;
+            }
+            pick -= plate.growth_rate;
+        }
+
+        let frontier = &mut plates[p].frontier;
+        let realm = frontier.swap_remove(rng.gen_range(0..frontier.len()));
+        if realm_plates[realm] != UNASSIGNED {
+            continue;
+        }
+
+        realm_plates[realm] = p;
+        for &neighbour in &adjacency[realm] {
+            if realm_plates[neighbour] == UNASSIGNED {
+                frontier.push(neighbour);
+            }
+        }
+    }
+}
+
+fn get_nearest_plate(site_point: &Point16, plates: &[Plate], realms: &[Realm]) -> usize {
+    let mut nearest_distance = f32::INFINITY;
+    let mut nearest_plate = 0;
+    for (p, plate) in plates.iter().enumerate() {
+        let distance = calculate_distance(site_point, &realms[plate.seed_realm].site_point);
+        if distance < nearest_distance {
+            nearest_distance = distance;
+            nearest_plate = p;
+        }
+    }
+    nearest_plate
+}
@@ -0,0 +1,233 @@
+use std::{
+    cmp::Ordering,
+    collections::{BinaryHeap, HashSet},
+};
+
+use gamescript::models::{
+    continent::{MapLevel, Planet, PlanetSettings},
+    point::{calculate_distance, Point16},
+    politics::{Nation, Politics},
+};
+use rand::{rngs::StdRng, seq::SliceRandom, Rng, SeedableRng};
+use world::coloring;
+
+use crate::{adjacency, naming_builder};
+
+pub const POLITICS_SALT: u64 = 0x5eed_0005;
+
+struct ProvinceInfo {
+    site_point: Point16,
+    // index of the continent in the sorted walk, for the naming style
+    continent: usize,
+    elevation: f32,
+    land_share: f32,
+    pixels: u32,
+}
+
+// cheapest claim first, ties go to the lower province and nation so the growth is deterministic
+#[derive(PartialEq)]
+struct Claim {
+    cost: f32,
+    province: usize,
+    nation: u16,
+}
+
+impl Eq for Claim {}
+
+impl Ord for Claim {
+    fn cmp(&self, other: &Self) -> Ordering {
+        other
+            .cost
+            .total_cmp(&self.cost)
+            .then_with(|| other.province.cmp(&self.province))
+            .then_with(|| other.nation.cmp(&self.nation))
+    }
+}
+
+impl PartialOrd for Claim {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+// Places capitals on spread out lowland provinces and grows every nation over the province
+// adjacency at once, each province goes to the nation that reaches it cheapest. Steps cost
+// more uphill, into mountains and over the sea, strong nations pay less for every step.
+pub fn build_politics(planet: &Planet, planet_settings: &PlanetSettings) -> Politics {
+    let settings = &planet_settings.politics_settings;
+    let keys = adjacency::get_sorted_continent_keys(&planet.continents);
+    let (owners, provinces_len) =
+        adjacency::build_owner_raster(&planet.img_size, &planet.continents, &keys, MapLevel::Province);
+    let adjacency = adjacency::build_adjacency(&planet.img_size, &owners, provinces_len, true);
+    let provinces = build_province_infos(planet, planet_settings, &keys);
+    let is_land: Vec<bool> = provinces.iter().map(|pv| pv.pixels > 0 && pv.land_share >= settings.min_land_share).collect();
+
+    let mut rng = StdRng::seed_from_u64(planet_settings.seed ^ POLITICS_SALT);
+    let capitals = place_capitals(&provinces, &is_land, planet_settings, &mut rng);
+    let strengths: Vec<f32> = capitals
+        .iter()
+        .map(|_| rng.gen_range(settings.strength_min..=settings.strength_max.max(settings.strength_min)))
+        .collect();
+
+    let mut costs: Vec<f32> = vec![f32::INFINITY; provinces_len];
+    let mut nations: Vec<Option<u16>> = vec![None; provinces_len];
+    let mut queue: BinaryHeap<Claim> = BinaryHeap::new();
+    for (n, &capital) in capitals.iter().enumerate() {
+        costs[capital] = 0.0;
+        nations[capital] = Some(n as u16);
+        queue.push(Claim { cost: 0.0, province: capital, nation: n as u16 });
+    }
+
+    while let Some(claim) = queue.pop() {
+        if claim.cost > costs[claim.province] || nations[claim.province] != Some(claim.nation) {
+            continue;
+        }
+        let from = &provinces[claim.province];
+        for &next in &adjacency[claim.province] {
+            let to = &provinces[next];
+            let mut step = 1.0 + settings.elevation_cost * (to.elevation - from.elevation).abs();
+            if !is_land[next] {
+                step += settings.water_cost;
+            } else if to.elevation > settings.mountain_elevation {
+                step += settings.mountain_cost;
+            }
+            let cost = claim.cost + step / strengths[claim.nation as usize];
+            if settings.max_cost > 0.0 && cost > settings.max_cost {
+                continue;
+            }
+            if cost < costs[next] {
+                costs[next] = cost;
+                nations[next] = Some(claim.nation);
+                queue.push(Claim { cost, province: next, nation: claim.nation });
+            }
+        }
+    }
+
+    // the sea only carried the growth
+    for (nation, land) in nations.iter_mut().zip(&is_land) {
+        if !land {
+            *nation = None;
+        }
+    }
+
+    let colors = get_nation_colors(&nations, &adjacency, capitals.len());
+    let generators = naming_builder::build_generators(&planet_settings.naming_settings);
+    let mut used: HashSet<String> = HashSet::new();
+    let nations_list: Vec<Nation> = capitals
+        .iter()
+        .enumerate()
+        .map(|(n, &capital)| {
+            let name = match get_style(planet, &keys, &provinces[capital], planet_settings) {
+                Some(style) => naming_builder::get_unique_name(
+                    &generators[style],
+                    &planet_settings.naming_settings,
+                    &mut rng,
+                    &mut used,
+                ),
+                None => format!("Nation {}", n + 1),
+            };
+            Nation {
+                name,
+                capital: capital as u32,
+                color: colors[n],
+                strength: strengths[n],
+                provinces: nations.iter().filter(|nation| **nation == Some(n as u16)).count() as u32,
+            }
+        })
+        .collect();
+
+    Politics { nations: nations_list, province_nations: nations }
+}
+
+fn build_province_infos(planet: &Planet, planet_settings: &PlanetSettings, keys: &[(u16, u16)]) -> Vec<ProvinceInfo> {
+    let sea_level = planet_settings.tectonic_settings.sea_level;
+    let mut provinces: Vec<ProvinceInfo> = Vec::new();
+    for (c, key) in keys.iter().enumerate() {
+        for rlm in &planet.continents[key].realms {
+            for pv in &rlm.provinces {
+                let (mut pixels, mut land_pixels, mut elevation_sum) = (0_u32, 0_u32, 0.0_f32);
+                for rg in &pv.regions {
+                    let elevation = planet.get_region_elevation(rg);
+                    let rg_pixels = rg.pixels.len() as u32;
+                    pixels += rg_pixels;
+                    elevation_sum += elevation * rg_pixels as f32;
+                    if elevation >= sea_level {
+                        land_pixels += rg_pixels;
+                    }
+                }
+                let share = |value: f32| if pixels > 0 { value / pixels as f32 } else { 0.0 };
+                provinces.push(ProvinceInfo {
+                    site_point: Point16::new(pv.site_point.x, pv.site_point.y),
+                    continent: c,
+                    elevation: share(elevation_sum),
+                    land_share: share(land_pixels as f32),
+                    pixels,
+                });
+            }
+        }
+    }
+    provinces
+}
+
+// Capitals go on random lowland provinces, as far apart as the land allows. The distance
+// starts at what an even share of the land would give and shrinks until all capitals fit.
+fn place_capitals(provinces: &[ProvinceInfo], is_land: &[bool], planet_settings: &PlanetSettings, rng: &mut StdRng) -> Vec<usize> {
+    let settings = &planet_settings.politics_settings;
+    let mut candidates: Vec<usize> = (0..provinces.len())
+        .filter(|&i| is_land[i] && provinces[i].elevation <= settings.mountain_elevation)
+        .collect();
+    candidates.shuffle(rng);
+    let count = (settings.nation_count as usize).min(candidates.len());
+
+    let land_pixels: u32 = candidates.iter().map(|&i| provinces[i].pixels).sum();
+    let mut min_distance = (land_pixels as f32 / count.max(1) as f32).sqrt();
+    let mut capitals: Vec<usize> = Vec::with_capacity(count);
+    while capitals.len() < count {
+        for &candidate in &candidates {
+            if capitals.len() == count {
+                break;
+            }
+            let is_far_enough = capitals.iter().all(|&capital| {
+                calculate_distance(&provinces[candidate].site_point, &provinces[capital].site_point) >= min_distance
+            });
+            if is_far_enough && !capitals.contains(&candidate) {
+                capitals.push(candidate);
+            }
+        }
+        min_distance *= 0.75;
+    }
+    capitals
+}
+
+// nations are named in the style of the continent their capital is on
+fn get_style(planet: &Planet, keys: &[(u16, u16)], capital: &ProvinceInfo, planet_settings: &PlanetSettings) -> Option<usize> {
+    let continent = &planet.continents[&keys[capital.continent]];
+    let styles = &planet_settings.naming_settings.styles;
+    styles
+        .iter()
+        .position(|style| style.name == continent.name_style)
+        .or(if styles.is_empty() { None } else { Some(0) })
+}
+
+// categorical colours over the nation borders, so neighbouring nations never share a colour
+fn get_nation_colors(nations: &[Option<u16>], adjacency: &[Vec<usize>], nations_len: usize) -> Vec<[u8; 3]> {
+    let mut nation_adjacency: Vec<Vec<usize>> = vec![Vec::new(); nations_len];
+    for (province, neighbours) in adjacency.iter().enumerate() {
+        let Some(nation) = nations[province] else { continue };
+        for &next in neighbours {
+            match nations[next] {
+                Some(other) if other != nation => nation_adjacency[nation as usize].push(other as usize),
+                _ => {}
+            }
+        }
+    }
+    for neighbours in &mut nation_adjacency {
+        neighbours.sort_unstable();
+        neighbours.dedup();
+    }
+
+    coloring::color_graph(&nation_adjacency, coloring::CATEGORICAL_COLORS.len())
+        .into_iter()
+        .map(coloring::get_categorical_color)
+        .collect()
+}
@@ -0,0 +1,225 @@
+use std::{
+    fmt,
+    io::{IsTerminal, Write},
+    sync::{
+        atomic::{AtomicU8, AtomicUsize, Ordering},
+        Mutex, OnceLock,
+    },
+    time::Instant,
+};
+
+use serde::Serialize;
+
+#[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Debug)]
+pub enum Verbosity {
+    Quiet,
+    Normal,
+    Verbose,
+    Debug,
+    Trace,
+}
+
+impl Verbosity {
+    pub const ALL: [Verbosity; 5] = [
+        Verbosity::Quiet,
+        Verbosity::Normal,
+        Verbosity::Verbose,
+        Verbosity::Debug,
+        Verbosity::Trace,
+    ];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Verbosity::Quiet => "quiet",
+            Verbosity::Normal => "normal",
+            Verbosity::Verbose => "verbose",
+            Verbosity::Debug => "debug",
+            Verbosity::Trace => "trace",
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<Verbosity> {
+        Verbosity::ALL.iter().copied().find(|verbosity| verbosity.name() == name)
+    }
+}
+
+static VERBOSITY: AtomicU8 = AtomicU8::new(Verbosity::Normal as u8);
+static DEPTH: AtomicUsize = AtomicUsize::new(0);
+static START: OnceLock<Instant> = OnceLock::new();
+static SPANS: Mutex<Vec<SpanTiming>> = Mutex::new(Vec::new());
+
+pub fn set_verbosity(verbosity: Verbosity) {
+    VERBOSITY.store(verbosity as u8, Ordering::Relaxed);
+}
+
+pub fn enabled(verbosity: Verbosity) -> bool {
+    verbosity != Verbosity::Quiet && verbosity as u8 <= VERBOSITY.load(Ordering::Relaxed)
+}
+
+pub fn log(verbosity: Verbosity, args: fmt::Arguments) {
+    if enabled(verbosity) {
+        println!("{}{}", indent(), args);
+    }
+}
+
+fn indent() -> String {
+    "  ".repeat(DEPTH.load(Ordering::Relaxed))
+}
+
+fn elapsed_ms(instant: Instant) -> f64 {
+    instant.elapsed().as_secs_f64() * 1000.0
+}
+
+#[derive(Serialize, Clone, Debug)]
+pub struct SpanTiming {
+    pub name: String,
+    pub depth: usize,
+    // offset from the start of the build
+    pub start_ms: f64,
+    pub duration_ms: f64,
+    pub items: Option<usize>,
+    pub note: Option<String>,
+}
+
+// A timed section of the build, spans opened inside it are nested under it.
+// The timing is recorded and printed when the span is dropped.
+pub struct Span {
+    index: usize,
+    start: Instant,
+    items: Option<usize>,
+    note: Option<String>,
+}
+
+pub fn span(name: &str) -> Span {
+    let start = Instant::now();
+    let build_start = *START.get_or_init(|| start);
+    let depth = DEPTH.fetch_add(1, Ordering::Relaxed);
+
+    let mut spans = SPANS.lock().unwrap();
+    spans.push(SpanTiming {
+        name: name.to_string(),
+        depth,
+        start_ms: (start - build_start).as_secs_f64() * 1000.0,
+        duration_ms: 0.0,
+        items: None,
+        note: None,
+    });
+    log(Verbosity::Debug, format_args!("{} ...", name));
+
+    Span { index: spans.len() - 1, start, items: None, note: None }
+}
+
+impl Span {
+    pub fn items(&mut self, items: usize) {
+        self.items = Some(items);
+    }
+
+    pub fn note(&mut self, note: &str) {
+        self.note = Some(note.to_string());
+    }
+}
+
+impl Drop for Span {
+    fn drop(&mut self) {
+        let duration_ms = elapsed_ms(self.start);
+        let depth = DEPTH.fetch_sub(1, Ordering::Relaxed) - 1;
+
+        let mut spans = SPANS.lock().unwrap();
+        let timing = &mut spans[self.index];
+        timing.duration_ms = duration_ms;
+        timing.items = self.items;
+        timing.note = self.note.take();
+
+        // top level spans are the summary, the nested ones are details
+        let verbosity = if depth == 0 { Verbosity::Normal } else { Verbosity::Verbose };
+        if enabled(verbosity) {
+            let mut line = format!("{}{} {:.1} ms", "  ".repeat(depth), timing.name, duration_ms);
+            if let Some(items) = timing.items {
+                line += &format!(", {} items", items);
+            }
+            if let Some(note) = &timing.note {
+                line += &format!(" ({})", note);
+            }
+            println!("{}", line);
+        }
+    }
+}
+
+#[derive(Serialize, Debug)]
+pub struct TimingReport {
+    pub version: String,
+    pub seed: u64,
+    pub total_ms: f64,
+    pub spans: Vec<SpanTiming>,
+}
+
+pub fn build_timing_report(seed: u64) -> TimingReport {
+    let spans = SPANS.lock().unwrap().clone();
+    let total_ms = START.get().map_or(0.0, |start| elapsed_ms(*start));
+    TimingReport {
+        version: env!("CARGO_PKG_VERSION").to_string(),
+        seed,
+        total_ms,
+        spans,
+    }
+}
+
+const BAR_WIDTH: usize = 32;
+
+// Progress bar for long loops, drawn on stderr only when it is a terminal
+// and redrawn once per percent so it costs nothing in tight loops.
+pub struct ProgressBar {
+    label: String,
+    total: usize,
+    current: usize,
+    drawn_percent: Option<usize>,
+    visible: bool,
+}
+
+impl ProgressBar {
+    pub fn new(label: &str, total: usize) -> ProgressBar {
+        ProgressBar {
+            label: label.to_string(),
+            total: total.max(1),
+            current: 0,
+            drawn_percent: None,
+            visible: enabled(Verbosity::Normal) && std::io::stderr().is_terminal(),
+        }
+    }
+
+    pub fn inc(&mut self) {
+        self.set(self.current + 1);
+    }
+
+    pub fn set(&mut self, current: usize) {
+        self.current = current.min(self.total);
+        if !self.visible {
+            return;
+        }
+
+        let percent = self.current * 100 / self.total;
+        if self.drawn_percent == Some(percent) {
+            return;
+        }
+        self.drawn_percent = Some(percent);
+
+        let filled = self.current * BAR_WIDTH / self.total;
+        let mut stderr = std::io::stderr();
+        let _ = write!(
+            stderr,
+            "\r{}{} [{}{}] {:>3}%",
+            indent(), self.label, "#".repeat(filled), "-".repeat(BAR_WIDTH - filled), percent
+        );
+        let _ = stderr.flush();
+    }
+}
+
+impl Drop for ProgressBar {
+    fn drop(&mut self) {
+        if self.visible && self.drawn_percent.is_some() {
+            // clear the bar, the span line reports the result
+            let len = indent().len() + self.label.len() + BAR_WIDTH + 8;
+            let _ = write!(std::io::stderr(), "\r{}\r", " ".repeat(len));
+        }
+    }
+}
@@ -0,0 +1,684 @@
+use std::collections::HashMap;
+
+use gamescript::models::{
+    continent::{Continent, MapLevel, PlanetSettings, Planet, Province, Realm, Region},
+    heightmap::EdgeMode,
+    point::{normalize_u8, Point16},
+    render::{self, Attribute, BlendMode, BorderSource, LayerKind, MarkerSource, Palette, ReliefSettings, RenderLayer},
+    settlement::SettlementKind,
+};
+use image::{ImageBuffer, Rgb, Rgba};
+use world::{coloring, font};
+
+use crate::{adjacency, hydrology_builder, noise_builder};
+
+struct Cell<'a> {
+    name: &'a str,
+    site_point: &'a Point16,
+}
+
+// Whatever a pipeline stage has to draw, flattened into the order the planet hierarchy is
+// walked. Early stages only know their lower levels, layers on a missing level draw nothing.
+pub struct RenderMap<'a> {
+    planet_settings: &'a PlanetSettings,
+    planet: Option<&'a Planet>,
+    regions: Vec<&'a Region>,
+    // the region of every pixel
+    region_owners: Vec<u32>,
+    // the cell every region belongs to at each level, by `MapLevel` order
+    region_cells: [Option<Vec<u32>>; 4],
+    cells: [Vec<Cell<'a>>; 4],
+}
+
+impl<'a> RenderMap<'a> {
+    fn new(planet_settings: &'a PlanetSettings, levels: usize) -> RenderMap<'a> {
+        RenderMap {
+            planet_settings,
+            planet: None,
+            regions: Vec::new(),
+            region_owners: Vec::new(),
+            region_cells: std::array::from_fn(|level| if level < levels { Some(Vec::new()) } else { None }),
+            cells: std::array::from_fn(|_| Vec::new()),
+        }
+    }
+
+    pub fn from_regions(planet_settings: &'a PlanetSettings, regions: &'a [Region]) -> RenderMap<'a> {
+        let mut map = RenderMap::new(planet_settings, 1);
+        regions.iter().for_each(|rg| map.push_region(rg));
+        map.finish()
+    }
+
+    pub fn from_provinces(planet_settings: &'a PlanetSettings, provinces: &'a [Province]) -> RenderMap<'a> {
+        let mut map = RenderMap::new(planet_settings, 2);
+        map.push_provinces(provinces);
+        map.finish()
+    }
+
+    pub fn from_realms(planet_settings: &'a PlanetSettings, realms: &'a [Realm]) -> RenderMap<'a> {
+        let mut map = RenderMap::new(planet_settings, 3);
+        map.push_realms(realms);
+        map.finish()
+    }
+
+    pub fn from_continents(
+        planet_settings: &'a PlanetSettings,
+        continents: &'a HashMap<(u16, u16), Continent>,
+    ) -> RenderMap<'a> {
+        let mut map = RenderMap::new(planet_settings, 4);
+        for key in adjacency::get_sorted_continent_keys(continents) {
+            let continent = &continents[&key];
+            map.push_cell(MapLevel::Continent, &continent.name, &continent.site_point);
+            map.push_realms(&continent.realms);
+        }
+        map.finish()
+    }
+
+    pub fn from_planet(planet_settings: &'a PlanetSettings, planet: &'a Planet) -> RenderMap<'a> {
+        let mut map = RenderMap::from_continents(planet_settings, &planet.continents);
+        map.planet = Some(planet);
+        map
+    }
+
+    fn push_realms(&mut self, realms: &'a [Realm]) {
+        for rlm in realms {
+            self.push_cell(MapLevel::Realm, &rlm.name, &rlm.site_point);
+            self.push_provinces(&rlm.provinces);
+        }
+    }
+
+    fn push_provinces(&mut self, provinces: &'a [Province]) {
+        for pv in provinces {
+            self.push_cell(MapLevel::Province, &pv.name, &pv.site_point);
+            pv.regions.iter().for_each(|rg| self.push_region(rg));
+        }
+    }
+
+    fn push_cell(&mut self, level: MapLevel, name: &'a str, site_point: &'a Point16) {
+        self.cells[level as usize].push(Cell { name, site_point });
+    }
+
+    // a region belongs to the last cell pushed on every level above it
+    fn push_region(&mut self, rg: &'a Region) {
+        self.push_cell(MapLevel::Region, "", &rg.site_point);
+        for (level, region_cells) in self.region_cells.iter_mut().enumerate() {
+            if let Some(region_cells) = region_cells {
+                region_cells.push(self.cells[level].len() as u32 - 1);
+            }
+        }
+        self.regions.push(rg);
+    }
+
+    fn finish(mut self) -> RenderMap<'a> {
+        let img_size = &self.planet_settings.img_size;
+        self.region_owners = vec![adjacency::NO_OWNER; img_size.width as usize * img_size.height as usize];
+        for (i, rg) in self.regions.iter().enumerate() {
+            for px in &rg.pixels {
+                self.region_owners[px.1 as usize * img_size.width as usize + px.0 as usize] = i as u32;
+            }
+        }
+        self
+    }
+
+    fn width(&self) -> usize {
+        self.planet_settings.img_size.width as usize
+    }
+
+    fn height(&self) -> usize {
+        self.planet_settings.img_size.height as usize
+    }
+
+    // owner of every pixel at the level, None when the map doesn't know the level
+    fn get_owners(&self, level: MapLevel) -> Option<Vec<u32>> {
+        let region_cells = self.region_cells[level as usize].as_ref()?;
+        let owners = self
+            .region_owners
+            .iter()
+            .map(|&region| if region == adjacency::NO_OWNER { region } else { region_cells[region as usize] })
+            .collect();
+        Some(owners)
+    }
+
+    // nation of every land pixel, None before the planet has its politics
+    fn get_nation_owners(&self) -> Option<Vec<u32>> {
+        let planet = self.planet?;
+        let politics = planet.politics.as_ref()?;
+        let provinces = self.get_owners(MapLevel::Province)?;
+        let sea_level = self.planet_settings.tectonic_settings.sea_level;
+        let elevations = planet.get_pixel_elevations();
+        let owners = provinces
+            .iter()
+            .zip(&elevations)
+            .map(|(&province, &elevation)| {
+                let nation = politics.province_nations.get(province as usize).copied().flatten();
+                match nation {
+                    Some(nation) if elevation >= sea_level => nation as u32,
+                    _ => adjacency::NO_OWNER,
+                }
+            })
+            .collect();
+        Some(owners)
+    }
+
+    fn get_border_owners(&self, source: BorderSource) -> Option<Vec<u32>> {
+        match source {
+            BorderSource::Level(level) => self.get_owners(level),
+            BorderSource::Nations => self.get_nation_owners(),
+        }
+    }
+}
+
+// Draws the layers bottom up onto a black canvas and saves the result as a PNG.
+pub fn render_image(map: &RenderMap, layers: &[RenderLayer], image_name: &str) {
+    render(map, layers).save(image_name).unwrap();
+}
+
+pub fn render(map: &RenderMap, layers: &[RenderLayer]) -> ImageBuffer<Rgb<u8>, Vec<u8>> {
+    let width = map.width();
+    let (canvas, _) = compose(map, layers);
+
+    let image = ImageBuffer::from_fn(width as u32, map.height() as u32, |x, y| {
+        let color = canvas[y as usize * width + x as usize];
+        Rgb(color.map(|channel| (channel.clamp(0.0, 1.0) * 255.0).round() as u8))
+    });
+    let items: Vec<LegendItem> = layers.iter().filter(|layer| layer.legend).flat_map(|layer| get_legend_items(map, layer)).collect();
+    if items.is_empty() {
+        return image;
+    }
+    add_legend(image, &items)
+}
+
+// Draws the layers onto a transparent canvas and saves the result as a PNG, for overlays to
+// put on top of other maps. Pixels no layer paints stay clear, there is no legend.
+pub fn render_overlay_image(map: &RenderMap, layers: &[RenderLayer], image_name: &str) {
+    let width = map.width();
+    let (canvas, coverage) = compose(map, layers);
+
+    let image: ImageBuffer<Rgba<u8>, Vec<u8>> = ImageBuffer::from_fn(width as u32, map.height() as u32, |x, y| {
+        let i = y as usize * width + x as usize;
+        // the canvas starts out black, so its colours are scaled by the coverage
+        let alpha = coverage[i];
+        let channel = |value: f32| if alpha > 0.0 { (value / alpha).clamp(0.0, 1.0) } else { 0.0 };
+        let [r, g, b] = canvas[i].map(|value| (channel(value) * 255.0).round() as u8);
+        Rgba([r, g, b, (alpha * 255.0).round() as u8])
+    });
+    image.save(image_name).unwrap();
+}
+
+// blends every layer into the canvas, returns the colours and how much of every pixel was painted
+fn compose(map: &RenderMap, layers: &[RenderLayer]) -> (Vec<[f32; 3]>, Vec<f32>) {
+    let (width, height) = (map.width(), map.height());
+    let mut canvas: Vec<[f32; 3]> = vec![[0.0; 3]; width * height];
+    let mut coverage: Vec<f32> = vec![0.0; width * height];
+
+    for layer in layers {
+        let mut paint: Vec<Option<[u8; 3]>> = vec![None; width * height];
+        match &layer.kind {
+            LayerKind::Fill { level, palette } => draw_fill(map, *level, palette, &mut paint),
+            LayerKind::Ramp { attribute, stops } => draw_ramp(map, *attribute, stops, &mut paint),
+            LayerKind::Borders { source, color, width } => draw_borders(map, *source, *color, *width, &mut paint),
+            LayerKind::Markers { source } => draw_markers(map, *source, &mut paint),
+            LayerKind::Biomes => draw_biomes(map, &mut paint),
+            LayerKind::Lakes { color } => draw_lakes(map, *color, &mut paint),
+            LayerKind::Rivers { color } => draw_rivers(map, *color, &mut paint),
+            LayerKind::Roads { color, shortcut_color } => draw_roads(map, *color, *shortcut_color, &mut paint),
+            LayerKind::Nations => draw_nations(map, &mut paint),
+            LayerKind::Labels { level, color } => draw_labels(map, *level, *color, &mut paint),
+            LayerKind::Hillshade { relief } => draw_hillshade(map, relief, &mut paint),
+        }
+
+        let opacity = layer.opacity.clamp(0.0, 1.0);
+        for ((base, alpha), top) in canvas.iter_mut().zip(&mut coverage).zip(&paint) {
+            if let Some(top) = top {
+                let top = top.map(|channel| channel as f32 / 255.0);
+                for c in 0..3 {
+                    let blended = blend(base[c], top[c], layer.blend);
+                    base[c] += (blended - base[c]) * opacity;
+                }
+                *alpha += (1.0 - *alpha) * opacity;
+            }
+        }
+    }
+
+    (canvas, coverage)
+}
+
+enum Swatch {
+    None,
+    Color([u8; 3]),
+    Ramp(Vec<(f32, [u8; 3])>),
+}
+
+struct LegendItem {
+    swatch: Swatch,
+    text: String,
+}
+
+const LEGEND_PADDING: u32 = 6;
+const LEGEND_ROW_HEIGHT: u32 = 13;
+const LEGEND_MAX_ROWS: u32 = 12;
+const SWATCH_SIZE: u32 = 9;
+const RAMP_WIDTH: u32 = 60;
+
+// A fill lists its named cells with their colours, or just the colours when the cells have
+// no names. A ramp shows its gradient between the first and last stop values.
+fn get_legend_items(map: &RenderMap, layer: &RenderLayer) -> Vec<LegendItem> {
+    match &layer.kind {
+        LayerKind::Fill { level, palette } => {
+            let colors = match get_cell_colors(map, *level, palette) {
+                Some(colors) => colors,
+                None => return Vec::new(),
+            };
+            let cells = &map.cells[*level as usize];
+            let title = format!("{} {}s", cells.len(), level.name());
+            let mut items: Vec<LegendItem> = vec![LegendItem { swatch: Swatch::None, text: title }];
+            if cells.iter().any(|cell| !cell.name.is_empty()) {
+                items.extend(cells.iter().zip(&colors).filter(|(cell, _)| !cell.name.is_empty()).map(|(cell, &color)| {
+                    LegendItem { swatch: Swatch::Color(color), text: cell.name.to_string() }
+                }));
+            } else {
+                let mut used: Vec<[u8; 3]> = Vec::new();
+                for color in colors {
+                    if !used.contains(&color) {
+                        used.push(color);
+                    }
+                }
+                items.extend(used.into_iter().map(|color| LegendItem { swatch: Swatch::Color(color), text: String::new() }));
+            }
+            items
+        }
+        LayerKind::Ramp { attribute, stops } if !stops.is_empty() => {
+            let (first, last) = (stops[0].0, stops[stops.len() - 1].0);
+            vec![LegendItem {
+                swatch: Swatch::Ramp(stops.clone()),
+                text: format!("{} {}..{}", attribute.name(), first, last),
+            }]
+        }
+        _ => Vec::new(),
+    }
+}
+
+// Lays the items out in rows on a dark strip below the map. Items that don't fit into
+// `LEGEND_MAX_ROWS` are left out and counted at the end.
+fn add_legend(image: ImageBuffer<Rgb<u8>, Vec<u8>>, items: &[LegendItem]) -> ImageBuffer<Rgb<u8>, Vec<u8>> {
+    let width = image.width();
+    let get_item_width = |item: &LegendItem| {
+        let swatch = match item.swatch {
+            Swatch::None => 0,
+            Swatch::Color(_) => SWATCH_SIZE + 3,
+            Swatch::Ramp(_) => RAMP_WIDTH + 3,
+        };
+        swatch + font::get_text_width(&item.text) + 10
+    };
+
+    let mut positions: Vec<(u32, u32)> = Vec::new();
+    let (mut x, mut row) = (LEGEND_PADDING, 0);
+    for item in items {
+        let item_width = get_item_width(item);
+        if x + item_width > width && x > LEGEND_PADDING {
+            x = LEGEND_PADDING;
+            row += 1;
+        }
+        // the last row keeps room for the count of the items left out
+        if row == LEGEND_MAX_ROWS - 1 && x + item_width + font::get_text_width("+00000 more") > width {
+            break;
+        }
+        positions.push((x, row));
+        x += item_width;
+    }
+    let rows = positions.last().map_or(0, |&(_, row)| row) + 1;
+    let height = image.height() + rows * LEGEND_ROW_HEIGHT + LEGEND_PADDING * 2;
+
+    let mut legend: ImageBuffer<Rgb<u8>, Vec<u8>> = ImageBuffer::from_pixel(width, height, Rgb([24, 24, 24]));
+    for (x, y, pixel) in image.enumerate_pixels() {
+        legend.put_pixel(x, y, *pixel);
+    }
+    let mut put = |x: u32, y: u32, color: [u8; 3]| {
+        if x < width && y < height {
+            legend.put_pixel(x, y, Rgb(color));
+        }
+    };
+    let draw_text = |put: &mut dyn FnMut(u32, u32, [u8; 3]), left: u32, top: u32, text: &str| {
+        for (px, py) in font::get_text_pixels(text) {
+            put(left + px, top + py, [230, 230, 230]);
+        }
+    };
+
+    for (item, &(left, row)) in items.iter().zip(&positions) {
+        let top = image.height() + LEGEND_PADDING + row * LEGEND_ROW_HEIGHT;
+        let mut left = left;
+        match &item.swatch {
+            Swatch::None => {}
+            Swatch::Color(color) => {
+                for y in 0..SWATCH_SIZE {
+                    for x in 0..SWATCH_SIZE {
+                        put(left + x, top + y, *color);
+                    }
+                }
+                left += SWATCH_SIZE + 3;
+            }
+            Swatch::Ramp(stops) => {
+                let (first, last) = (stops[0].0, stops[stops.len() - 1].0);
+                for x in 0..RAMP_WIDTH {
+                    let color = render::sample_ramp(stops, first + (last - first) * x as f32 / (RAMP_WIDTH - 1) as f32);
+                    for y in 0..SWATCH_SIZE {
+                        put(left + x, top + y, color);
+                    }
+                }
+                left += RAMP_WIDTH + 3;
+            }
+        }
+        draw_text(&mut put, left, top + 1, &item.text);
+    }
+    if positions.len() < items.len() {
+        let (left, row) = positions.last().map_or((LEGEND_PADDING, 0), |&(x, row)| (x + get_item_width(&items[positions.len() - 1]), row));
+        let top = image.height() + LEGEND_PADDING + row * LEGEND_ROW_HEIGHT;
+        draw_text(&mut put, left, top + 1, &format!("+{} more", items.len() - positions.len()));
+    }
+
+    legend
+}
+
+fn blend(base: f32, top: f32, mode: BlendMode) -> f32 {
+    match mode {
+        BlendMode::Normal => top,
+        BlendMode::Multiply => base * top,
+        BlendMode::Screen => 1.0 - (1.0 - base) * (1.0 - top),
+        BlendMode::Overlay if base < 0.5 => 2.0 * base * top,
+        BlendMode::Overlay => 1.0 - 2.0 * (1.0 - base) * (1.0 - top),
+        BlendMode::Add => (base + top).min(1.0),
+    }
+}
+
+fn draw_fill(map: &RenderMap, level: MapLevel, palette: &Palette, paint: &mut [Option<[u8; 3]>]) {
+    let (owners, colors) = match (map.get_owners(level), get_cell_colors(map, level, palette)) {
+        (Some(owners), Some(colors)) => (owners, colors),
+        _ => return,
+    };
+    for (pixel, &owner) in paint.iter_mut().zip(&owners) {
+        if owner != adjacency::NO_OWNER {
+            *pixel = Some(colors[owner as usize]);
+        }
+    }
+}
+
+// colour of every cell of the level, None when the map doesn't know the level
+fn get_cell_colors(map: &RenderMap, level: MapLevel, palette: &Palette) -> Option<Vec<[u8; 3]>> {
+    let owners = map.get_owners(level)?;
+    let len = map.cells[level as usize].len();
+    match palette {
+        Palette::Categorical => {
+            let img_size = &map.planet_settings.img_size;
+            let adjacency = adjacency::build_adjacency(img_size, &owners, len, true);
+            let colors = coloring::color_graph(&adjacency, coloring::CATEGORICAL_COLORS.len());
+            Some(colors.into_iter().map(coloring::get_categorical_color).collect())
+        }
+        Palette::Fixed(colors) if colors.is_empty() => None,
+        Palette::Fixed(colors) => Some((0..len).map(|i| colors[i % colors.len()]).collect()),
+    }
+}
+
+fn draw_ramp(map: &RenderMap, attribute: Attribute, stops: &[(f32, [u8; 3])], paint: &mut [Option<[u8; 3]>]) {
+    let width = map.width();
+    let pixel_noise = noise_builder::build_pixel_noise(map.planet_settings);
+    let heightmap = map.planet.and_then(|planet| planet.heightmap.as_ref());
+
+    for (i, &region) in map.region_owners.iter().enumerate() {
+        if region == adjacency::NO_OWNER {
+            continue;
+        }
+        let rg = map.regions[region as usize];
+        let (x, y) = ((i % width) as u16, (i / width) as u16);
+        let value = match attribute {
+            Attribute::Elevation => match heightmap {
+                Some(heightmap) => heightmap.get(x, y),
+                None => {
+                    let grey = noise_builder::get_pixel_grey_value(&pixel_noise, map.planet_settings, rg.grey_value, x, y);
+                    normalize_u8(grey as f64) as f32
+                }
+            },
+            Attribute::Temperature => rg.temperature,
+            Attribute::Precipitation => rg.precipitation,
+            Attribute::Resource(resource) => rg.resources.get(resource),
+        };
+        if stops.first().is_some_and(|first| value >= first.0) {
+            paint[i] = Some(render::sample_ramp(stops, value));
+        }
+    }
+}
+
+// only once the planet has its heightmap
+fn draw_hillshade(map: &RenderMap, relief: &ReliefSettings, paint: &mut [Option<[u8; 3]>]) {
+    let heightmap = match map.planet.and_then(|planet| planet.heightmap.as_ref()) {
+        Some(heightmap) => heightmap,
+        None => return,
+    };
+    let width = map.width();
+    let lights = relief.get_lights();
+    // east and west meet on the planet, so the shading has no seam there
+    for (i, pixel) in paint.iter_mut().enumerate() {
+        let (x, y) = ((i % width) as u16, (i / width) as u16);
+        let normal = heightmap.get_normal(x, y, [relief.height_scale; 2], EdgeMode::WrapHorizontal);
+        let grey = (relief.get_shade(normal, &lights) * 255.0).round() as u8;
+        *pixel = Some([grey; 3]);
+    }
+}
+
+fn draw_borders(map: &RenderMap, source: BorderSource, color: [u8; 3], line_width: u8, paint: &mut [Option<[u8; 3]>]) {
+    let owners = match map.get_border_owners(source) {
+        Some(owners) => owners,
+        None => return,
+    };
+    // nations only meet each other, their coasts and the unclaimed land are left to the layers below
+    let outline_unowned = source != BorderSource::Nations;
+    let mask = build_border_mask(&owners, map.width(), map.height(), line_width, outline_unowned);
+    for (pixel, _) in paint.iter_mut().zip(&mask).filter(|(_, &is_border)| is_border) {
+        *pixel = Some(color);
+    }
+}
+
+// Pixels within `line_width` of an edge between two cells. The one pixel line runs along the
+// left or upper side of every edge and is widened on both sides, half a pixel more to the
+// right and below for even widths. Without `outline_unowned` only edges between two owners count.
+fn build_border_mask(owners: &[u32], width: usize, height: usize, line_width: u8, outline_unowned: bool) -> Vec<bool> {
+    let mut mask: Vec<bool> = vec![false; width * height];
+    if line_width == 0 {
+        return mask;
+    }
+    for y in 0..height {
+        for x in 0..width {
+            let owner = owners[y * width + x];
+            let is_edge = |other: u32| {
+                let owned = [owner, other].map(|cell| cell != adjacency::NO_OWNER);
+                other != owner && if outline_unowned { owned[0] || owned[1] } else { owned[0] && owned[1] }
+            };
+            mask[y * width + x] = (x + 1 < width && is_edge(owners[y * width + x + 1]))
+                || (y + 1 < height && is_edge(owners[(y + 1) * width + x]));
+        }
+    }
+    if line_width == 1 {
+        return mask;
+    }
+
+    // square dilation, once along the rows and once along the columns
+    let (before, after) = ((line_width as i32 - 1) / 2, line_width as i32 / 2);
+    let dilate = |mask: &[bool], step: (i32, i32)| -> Vec<bool> {
+        let mut dilated: Vec<bool> = vec![false; width * height];
+        for y in 0..height as i32 {
+            for x in 0..width as i32 {
+                if !mask[y as usize * width + x as usize] {
+                    continue;
+                }
+                for offset in -before..=after {
+                    let (nx, ny) = (x + step.0 * offset, y + step.1 * offset);
+                    if nx >= 0 && ny >= 0 && nx < width as i32 && ny < height as i32 {
+                        dilated[ny as usize * width + nx as usize] = true;
+                    }
+                }
+            }
+        }
+        dilated
+    };
+    let rows = dilate(&mask, (1, 0));
+    dilate(&rows, (0, 1))
+}
+
+fn draw_markers(map: &RenderMap, source: MarkerSource, paint: &mut [Option<[u8; 3]>]) {
+    let planet = match map.planet {
+        Some(planet) => planet,
+        None => return,
+    };
+    // centre, outline radius, radius of the fill and its colour
+    let markers: Vec<(Point16, i32, i32, [u8; 3])> = match source {
+        MarkerSource::Settlements => planet
+            .settlements
+            .iter()
+            .map(|settlement| {
+                let (outer, inner, fill) = match settlement.kind {
+                    SettlementKind::City => (3, 1, [220, 40, 40]),
+                    SettlementKind::Town => (2, 0, [250, 170, 40]),
+                    SettlementKind::Village => (1, 0, [250, 250, 250]),
+                };
+                (Point16::new(settlement.position.0, settlement.position.1), outer, inner, fill)
+            })
+            .collect(),
+        MarkerSource::Capitals => match &planet.politics {
+            Some(politics) => politics
+                .nations
+                .iter()
+                .filter_map(|nation| {
+                    let cell = map.cells[MapLevel::Province as usize].get(nation.capital as usize)?;
+                    Some((Point16::new(cell.site_point.x, cell.site_point.y), 4, 2, nation.color))
+                })
+                .collect(),
+            None => Vec::new(),
+        },
+    };
+
+    let (width, height) = (map.width() as i32, map.height() as i32);
+    for (center, outer, inner, fill) in markers {
+        let (cx, cy) = (center.x as i32, center.y as i32);
+        for y in (cy - outer)..=(cy + outer) {
+            for x in (cx - outer)..=(cx + outer) {
+                if x < 0 || y < 0 || x >= width || y >= height {
+                    continue;
+                }
+                let is_inner = (x - cx).abs() <= inner && (y - cy).abs() <= inner;
+                paint[(y * width + x) as usize] = Some(if is_inner { fill } else { [20, 20, 20] });
+            }
+        }
+    }
+}
+
+fn draw_biomes(map: &RenderMap, paint: &mut [Option<[u8; 3]>]) {
+    for (pixel, &region) in paint.iter_mut().zip(&map.region_owners) {
+        if region != adjacency::NO_OWNER {
+            let color = map.regions[region as usize].biome.color();
+            *pixel = Some([color.r, color.g, color.b]);
+        }
+    }
+}
+
+// only once the planet has its hydrology
+fn draw_lakes(map: &RenderMap, color: [u8; 3], paint: &mut [Option<[u8; 3]>]) {
+    let hydrology = match map.planet.and_then(|planet| planet.hydrology.as_ref()) {
+        Some(hydrology) => hydrology,
+        None => return,
+    };
+    let width = map.width();
+    for &region in hydrology.lakes.iter().flat_map(|lake| &lake.regions) {
+        for px in &map.regions[region as usize].pixels {
+            paint[px.1 as usize * width + px.0 as usize] = Some(color);
+        }
+    }
+}
+
+fn draw_rivers(map: &RenderMap, color: [u8; 3], paint: &mut [Option<[u8; 3]>]) {
+    let hydrology = match map.planet.and_then(|planet| planet.hydrology.as_ref()) {
+        Some(hydrology) => hydrology,
+        None => return,
+    };
+    for river in &hydrology.rivers {
+        let radius = (river.strahler_order as i32 - 1) / 2;
+        for px in &river.pixels {
+            put_square(map, (px.0 as i32, px.1 as i32), radius, color, paint);
+        }
+    }
+}
+
+// shortcuts in their own colour, so the spanning tree stands out
+fn draw_roads(map: &RenderMap, color: [u8; 3], shortcut_color: [u8; 3], paint: &mut [Option<[u8; 3]>]) {
+    let planet = match map.planet {
+        Some(planet) => planet,
+        None => return,
+    };
+    for road in planet.roads.iter().filter(|road| !road.regions.is_empty()) {
+        let first = &map.regions[road.regions[0] as usize].site_point;
+        let mut pixels: Vec<(u16, u16)> = vec![(first.x, first.y)];
+        for pair in road.regions.windows(2) {
+            let (from, to) = (&map.regions[pair[0] as usize].site_point, &map.regions[pair[1] as usize].site_point);
+            let (x0, y0, x1, y1) = (from.x as i32, from.y as i32, to.x as i32, to.y as i32);
+            hydrology_builder::trace_line(x0, y0, x1, y1, map.width() as i32, &mut pixels);
+        }
+        let color = if road.is_shortcut { shortcut_color } else { color };
+        for px in pixels {
+            paint[px.1 as usize * map.width() + px.0 as usize] = Some(color);
+        }
+    }
+}
+
+fn draw_nations(map: &RenderMap, paint: &mut [Option<[u8; 3]>]) {
+    let (owners, politics) = match (map.get_nation_owners(), map.planet.and_then(|planet| planet.politics.as_ref())) {
+        (Some(owners), Some(politics)) => (owners, politics),
+        _ => return,
+    };
+    for (pixel, &nation) in paint.iter_mut().zip(&owners) {
+        if nation != adjacency::NO_OWNER {
+            *pixel = Some(politics.nations[nation as usize].color);
+        }
+    }
+}
+
+// a square of `radius` pixels around the centre, cut off at the map edge
+fn put_square(map: &RenderMap, center: (i32, i32), radius: i32, color: [u8; 3], paint: &mut [Option<[u8; 3]>]) {
+    let (width, height) = (map.width() as i32, map.height() as i32);
+    for y in (center.1 - radius)..=(center.1 + radius) {
+        for x in (center.0 - radius)..=(center.0 + radius) {
+            if x >= 0 && y >= 0 && x < width && y < height {
+                paint[(y * width + x) as usize] = Some(color);
+            }
+        }
+    }
+}
+
+// Names centred on the cell sites with a one pixel halo in black or white, whichever
+// stands out more against the text colour, so they stay readable on any fill.
+fn draw_labels(map: &RenderMap, level: MapLevel, color: [u8; 3], paint: &mut [Option<[u8; 3]>]) {
+    if map.region_cells[level as usize].is_none() {
+        return;
+    }
+    let luminance = 0.299 * color[0] as f32 + 0.587 * color[1] as f32 + 0.114 * color[2] as f32;
+    let halo = if luminance > 127.0 { [0, 0, 0] } else { [255, 255, 255] };
+    let (width, height) = (map.width() as i32, map.height() as i32);
+
+    for cell in map.cells[level as usize].iter().filter(|cell| !cell.name.is_empty()) {
+        let left = cell.site_point.x as i32 - font::get_text_width(cell.name) as i32 / 2;
+        let top = cell.site_point.y as i32 - font::GLYPH_HEIGHT as i32 / 2;
+        let pixels: Vec<(i32, i32)> = font::get_text_pixels(cell.name)
+            .iter()
+            .map(|&(x, y)| (left + x as i32, top + y as i32))
+            .collect();
+        let mut put = |x: i32, y: i32, color: [u8; 3]| {
+            if x >= 0 && y >= 0 && x < width && y < height {
+                paint[(y * width + x) as usize] = Some(color);
+            }
+        };
+        for &(x, y) in &pixels {
+            for (dx, dy) in [(-1, 0), (1, 0), (0, -1), (0, 1)] {
+                put(x + dx, y + dy, halo);
+            }
+        }
+        for &(x, y) in &pixels {
+            put(x, y, color);
+        }
+    }
+}
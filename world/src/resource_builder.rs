@@ -0,0 +1,160 @@
+use std::collections::HashMap;
+
+use gamescript::{
+    models::{
+        climate::Biome,
+        continent::{Planet, PlanetSettings, Region},
+        point::calculate_wrapped_distance,
+        resource::{Resource, ResourceSettings, Resources},
+        tectonics::BoundaryType,
+    },
+    pathfinding,
+};
+use world::noise::Noise;
+
+use crate::{adjacency, noise_builder};
+
+pub const RESOURCE_SALT: u64 = 0x5eed_0007;
+
+// Places resources on every region from its terrain, then sums them up per province and realm.
+// Metals sit along plate boundaries and in the mountains, stone in the highlands, timber in
+// forests, fertile soil on low habitable land near rivers and fish in the sea near the coast.
+// Each amount is multiplied with its own cluster noise, so deposits come in patches.
+pub fn apply_resources(planet: &mut Planet, planet_settings: &PlanetSettings) {
+    let amounts = build_region_resources(planet, planet_settings);
+
+    let keys = adjacency::get_sorted_continent_keys(&planet.continents);
+    let mut i: usize = 0;
+    for key in &keys {
+        if let Some(continent) = planet.continents.get_mut(key) {
+            for rlm in &mut continent.realms {
+                rlm.resources = Resources::default();
+                for pv in &mut rlm.provinces {
+                    pv.resources = Resources::default();
+                    for rg in &mut pv.regions {
+                        rg.resources = amounts[i];
+                        pv.resources.add(&rg.resources);
+                        i += 1;
+                    }
+                    rlm.resources.add(&pv.resources);
+                }
+            }
+        }
+    }
+}
+
+fn build_region_resources(planet: &Planet, planet_settings: &PlanetSettings) -> Vec<Resources> {
+    let settings = &planet_settings.resource_settings;
+    let sea_level = planet_settings.tectonic_settings.sea_level;
+    let keys = adjacency::get_sorted_continent_keys(&planet.continents);
+    let regions = adjacency::get_sorted_regions(&planet.continents, &keys);
+    let (owners, regions_len) = adjacency::build_regions_owner_raster(&planet.img_size, &planet.continents, &keys);
+    let adjacency = adjacency::build_adjacency(&planet.img_size, &owners, regions_len, true);
+
+    let water = adjacency::build_region_water(planet, &regions, &adjacency, sea_level);
+    let (elevations, is_sea) = (&water.elevations, &water.is_sea);
+    let ores = build_boundary_ores(planet, settings, &keys, &regions, &adjacency);
+
+    let noises: Vec<Noise> = Resource::ALL
+        .iter()
+        .map(|&resource| {
+            let seed = noise_builder::get_noise_seed(planet_settings, RESOURCE_SALT + resource as u64);
+            Noise::new(seed, &settings.cluster_noise, planet.img_size.width)
+        })
+        .collect();
+    let fade = |distance: u32, falloff: f32| 0.5_f32.powf(distance as f32 / falloff.max(f32::EPSILON));
+
+    (0..regions_len)
+        .map(|i| {
+            let rg = regions[i];
+            let mut resources = Resources::default();
+            if rg.pixels.is_empty() {
+                return resources;
+            }
+
+            let height = ((elevations[i] - sea_level) / (1.0 - sea_level).max(f32::EPSILON)).clamp(0.0, 1.0);
+            let mountains = ((elevations[i] - settings.mountain_elevation)
+                / (1.0 - settings.mountain_elevation).max(f32::EPSILON))
+            .clamp(0.0, 1.0);
+            let base = |resource: Resource| -> f32 {
+                match resource {
+                    _ if is_sea[i] && resource != Resource::Fish => 0.0,
+                    Resource::Metals => ores[i].max(mountains),
+                    Resource::Stone => (0.3 + 0.7 * height).max(ores[i] * 0.5),
+                    Resource::Timber => rg.biome.timber(),
+                    Resource::FertileSoil => {
+                        rg.biome.habitability() * (1.0 - height) * (0.5 + 0.5 * fade(water.fresh_water_distances[i], settings.river_falloff))
+                    }
+                    Resource::Fish => match rg.biome {
+                        Biome::Lake => 0.6,
+                        _ if is_sea[i] => fade(water.land_distances[i].saturating_sub(1), settings.fish_falloff),
+                        _ if water.is_fresh_water[i] => 0.3,
+                        _ => 0.0,
+                    },
+                }
+            };
+
+            for (resource, noise) in Resource::ALL.iter().zip(&noises) {
+                let amount = base(*resource) * get_cluster(noise, settings, rg);
+                resources.set(*resource, if amount >= settings.min_amount { amount } else { 0.0 });
+            }
+            resources
+        })
+        .collect()
+}
+
+// noise at the region site mapped to 0..1, with everything below the threshold cut away
+fn get_cluster(noise: &Noise, settings: &ResourceSettings, rg: &Region) -> f32 {
+    let value = (noise.sample(rg.site_point.x as f32, rg.site_point.y as f32) * 0.5 + 0.5).clamp(0.0, 1.0);
+    ((value - settings.cluster_threshold) / (1.0 - settings.cluster_threshold).max(f32::EPSILON)).max(0.0)
+}
+
+// Ore richness of every region from the plate boundaries, fading out with the site distance
+// to the nearest border region of each boundary type. The richest boundary wins.
+fn build_boundary_ores(
+    planet: &Planet,
+    settings: &ResourceSettings,
+    keys: &[(u16, u16)],
+    regions: &[&Region],
+    adjacency: &[Vec<usize>],
+) -> Vec<f32> {
+    let mut region_plates: Vec<usize> = Vec::with_capacity(regions.len());
+    for (p, key) in keys.iter().enumerate() {
+        let len: usize = planet.continents[key].realms.iter().flat_map(|rlm| &rlm.provinces).map(|pv| pv.regions.len()).sum();
+        region_plates.extend(std::iter::repeat_n(p, len));
+    }
+    let plate_indexes: HashMap<(u16, u16), usize> = keys.iter().enumerate().map(|(p, &key)| (key, p)).collect();
+    let boundary_types: HashMap<(usize, usize), BoundaryType> = planet
+        .plate_boundaries
+        .iter()
+        .map(|boundary| {
+            let (a, b) = (plate_indexes[&boundary.plates.0], plate_indexes[&boundary.plates.1]);
+            ((a.min(b), a.max(b)), boundary.boundary_type)
+        })
+        .collect();
+
+    let width = planet.img_size.width;
+    let distance = |a: usize, b: usize| Some(calculate_wrapped_distance(&regions[a].site_point, &regions[b].site_point, width));
+    let mut ores: Vec<f32> = vec![0.0; regions.len()];
+    for (boundary_type, richness) in [
+        (BoundaryType::Convergent, settings.convergent_metals),
+        (BoundaryType::Divergent, settings.divergent_metals),
+        (BoundaryType::Transform, settings.transform_metals),
+    ] {
+        let sources: Vec<usize> = (0..regions.len())
+            .filter(|&a| {
+                adjacency[a].iter().any(|&b| {
+                    let (plate_a, plate_b) = (region_plates[a], region_plates[b]);
+                    plate_a != plate_b
+                        && boundary_types.get(&(plate_a.min(plate_b), plate_a.max(plate_b))) == Some(&boundary_type)
+                })
+            })
+            .collect();
+        let distances = pathfinding::find_costs(adjacency, &sources, distance, Some(settings.boundary_distance));
+        for (ore, distance) in ores.iter_mut().zip(distances) {
+            let falloff = (1.0 - distance / settings.boundary_distance.max(f32::EPSILON)).max(0.0);
+            *ore = ore.max(richness * falloff);
+        }
+    }
+    ores
+}
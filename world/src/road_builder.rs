@@ -0,0 +1,253 @@
+use std::collections::HashMap;
+
+use gamescript::{
+    models::{
+        climate::Biome,
+        continent::{Planet, PlanetSettings, Region},
+        point::calculate_wrapped_distance,
+        road::Road,
+    },
+    pathfinding,
+};
+
+use crate::adjacency;
+
+// Candidate road between two settlements, with the cost of its own best route.
+struct Link {
+    from: usize,
+    to: usize,
+    cost: f32,
+}
+
+// what a step onto a region costs, shared by every road search
+struct Terrain<'a> {
+    regions: Vec<&'a Region>,
+    adjacency: Vec<Vec<usize>>,
+    elevations: Vec<f32>,
+    is_blocked: Vec<bool>,
+    is_river: Vec<bool>,
+    width: u16,
+}
+
+// Connects the settlements with a spanning tree of the cheapest routes between near
+// neighbours, plus shortcuts where the tree makes a long detour. Roads are routed one by
+// one with A* over the region graph, and regions that already carry a road are cheaper,
+// so later roads join the earlier ones instead of running next to them.
+pub fn build_roads(planet: &Planet, planet_settings: &PlanetSettings) -> Vec<Road> {
+    let settings = &planet_settings.road_settings;
+    let terrain = build_terrain(planet, planet_settings);
+    let sites: Vec<usize> = planet.settlements.iter().map(|settlement| settlement.region as usize).collect();
+    let no_roads: Vec<bool> = vec![false; terrain.regions.len()];
+
+    let mut links: Vec<Link> = Vec::new();
+    for (from, to) in get_neighbour_pairs(&terrain, &sites, settings.neighbours as usize) {
+        if let Some((_, cost)) = find_route(&terrain, &no_roads, planet_settings, sites[from], sites[to]) {
+            links.push(Link { from, to, cost });
+        }
+    }
+    links.sort_by(|a, b| a.cost.total_cmp(&b.cost).then((a.from, a.to).cmp(&(b.from, b.to))));
+
+    // kruskal, then shortcuts where the tree route is far longer than the direct one
+    let mut parents: Vec<usize> = (0..sites.len()).collect();
+    let mut network: Vec<Vec<usize>> = vec![Vec::new(); sites.len()];
+    let mut network_costs: HashMap<(usize, usize), f32> = HashMap::new();
+    let mut chosen: Vec<(&Link, bool)> = Vec::new();
+    for link in &links {
+        let (a, b) = (find_root(&mut parents, link.from), find_root(&mut parents, link.to));
+        if a != b {
+            parents[a] = b;
+            add_network_link(&mut network, &mut network_costs, link);
+            chosen.push((link, false));
+        }
+    }
+    for link in &links {
+        if network_costs.contains_key(&(link.from, link.to)) {
+            continue;
+        }
+        let cost = |a: usize, b: usize| network_costs.get(&(a, b)).copied();
+        let detour = pathfinding::find_path(&network, link.from, link.to, cost, |_| 0.0).map(|(_, cost)| cost);
+        if detour.is_none_or(|detour| detour > link.cost * settings.shortcut_ratio) {
+            add_network_link(&mut network, &mut network_costs, link);
+            chosen.push((link, true));
+        }
+    }
+
+    let mut has_road: Vec<bool> = vec![false; terrain.regions.len()];
+    let mut roads: Vec<Road> = Vec::with_capacity(chosen.len());
+    for (link, is_shortcut) in chosen {
+        let route = find_route(&terrain, &has_road, planet_settings, sites[link.from], sites[link.to]);
+        if let Some((path, cost)) = route {
+            for &region in &path {
+                has_road[region] = true;
+            }
+            roads.push(Road {
+                from: link.from as u32,
+                to: link.to as u32,
+                regions: path.iter().map(|&region| region as u32).collect(),
+                is_shortcut,
+                cost,
+            });
+        }
+    }
+
+    roads
+}
+
+fn build_terrain<'a>(planet: &'a Planet, planet_settings: &PlanetSettings) -> Terrain<'a> {
+    let sea_level = planet_settings.tectonic_settings.sea_level;
+    let keys = adjacency::get_sorted_continent_keys(&planet.continents);
+    let regions = adjacency::get_sorted_regions(&planet.continents, &keys);
+    let (owners, regions_len) = adjacency::build_regions_owner_raster(&planet.img_size, &planet.continents, &keys);
+    let adjacency = adjacency::build_adjacency(&planet.img_size, &owners, regions_len, true);
+    let elevations: Vec<f32> = regions.iter().map(|rg| planet.get_region_elevation(rg)).collect();
+    let is_blocked: Vec<bool> = regions
+        .iter()
+        .zip(&elevations)
+        .map(|(rg, &elevation)| elevation < sea_level || rg.biome == Biome::Lake)
+        .collect();
+    let mut is_river: Vec<bool> = vec![false; regions_len];
+    if let Some(hydrology) = &planet.hydrology {
+        for &region in hydrology.rivers.iter().flat_map(|river| &river.regions) {
+            is_river[region as usize] = true;
+        }
+    }
+
+    Terrain { regions, adjacency, elevations, is_blocked, is_river, width: planet.img_size.width }
+}
+
+// A* over the land regions. Steps cost their length, scaled up by the slope and the biome,
+// river crossings add a bridge and regions with a road are discounted. Every multiplier is
+// at least 1 apart from the road discount, so the straight line times it never overestimates.
+fn find_route(
+    terrain: &Terrain,
+    has_road: &[bool],
+    planet_settings: &PlanetSettings,
+    start: usize,
+    goal: usize,
+) -> Option<(Vec<usize>, f32)> {
+    let settings = &planet_settings.road_settings;
+    let distance = |a: usize, b: usize| {
+        calculate_wrapped_distance(&terrain.regions[a].site_point, &terrain.regions[b].site_point, terrain.width)
+    };
+    let cost = |from: usize, to: usize| -> Option<f32> {
+        if terrain.is_blocked[to] {
+            return None;
+        }
+        let slope = (terrain.elevations[to] - terrain.elevations[from]).abs();
+        let mut step = distance(from, to) * (1.0 + settings.elevation_cost * slope) * terrain.regions[to].biome.travel_cost();
+        if terrain.is_river[to] {
+            step += settings.river_cost;
+        }
+        if has_road[to] {
+            step *= settings.reuse_factor;
+        }
+        Some(step)
+    };
+    let discount = settings.reuse_factor.min(1.0);
+    pathfinding::find_path(&terrain.adjacency, start, goal, cost, |node| distance(node, goal) * discount)
+}
+
+// every settlement paired with its nearest others by straight distance, each pair once
+fn get_neighbour_pairs(terrain: &Terrain, sites: &[usize], neighbours: usize) -> Vec<(usize, usize)> {
+    let mut pairs: Vec<(usize, usize)> = Vec::new();
+    for (i, &site) in sites.iter().enumerate() {
+        let point = &terrain.regions[site].site_point;
+        let mut others: Vec<(f32, usize)> = sites
+            .iter()
+            .enumerate()
+            .filter(|(j, _)| *j != i)
+            .map(|(j, &other)| {
+                (calculate_wrapped_distance(point, &terrain.regions[other].site_point, terrain.width), j)
+            })
+            .collect();
+        others.sort_by(|a, b| a.0.total_cmp(&b.0).then(a.1.cmp(&b.1)));
+        pairs.extend(others.iter().take(neighbours).map(|&(_, j)| (i.min(j), i.max(j))));
+    }
+    pairs.sort_unstable();
+    pairs.dedup();
+    pairs
+}
+
+fn add_network_link(network: &mut [Vec<usize>], network_costs: &mut HashMap<(usize, usize), f32>, link: &Link) {
+    network[link.from].push(link.to);
+    network[link.to].push(link.from);
+    network_costs.insert((link.from, link.to), link.cost);
+    network_costs.insert((link.to, link.from), link.cost);
+}
+
+fn find_root(parents: &mut [usize], mut node: usize) -> usize {
+    while parents[node] != node {
+        parents[node] = parents[parents[node]];
+        node = parents[node];
+    }
+    node
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_planet;
+    use gamescript::models::settlement::{Settlement, SettlementKind};
+
+    const GRID: (u16, u16) = (20, 5);
+
+    fn get_region(x: u16, y: u16) -> u32 {
+        (y * GRID.0 + x) as u32
+    }
+
+    // flat grassland of 10 pixel cells, every step between neighbouring sites costs 10.
+    // Settlement 0, 1 and 3 sit on row 1 four cells apart, 2 two rows below between 0 and 1:
+    //   0 . . . 1 . . . 3
+    //   . . . . . . . . .
+    //   . . 2 . . . . . .
+    fn build_planet() -> Planet {
+        let mut planet = test_planet::build_grid_planet(GRID, 10, |_, _| (150, Biome::TemperateGrassland));
+        for (x, y) in [(1, 1), (5, 1), (3, 3), (9, 1)] {
+            planet.settlements.push(Settlement {
+                name: format!("{} {}", x, y),
+                kind: SettlementKind::Town,
+                position: (x * 10 + 5, y * 10 + 5),
+                region: get_region(x, y),
+                province: get_region(x, y),
+                realm: 0,
+                score: 1.0,
+            });
+        }
+        planet
+    }
+
+    #[test]
+    fn network_is_a_spanning_tree_with_shortcuts_for_long_detours() {
+        let planet = build_planet();
+        let planet_settings = test_planet::build_planet_settings(&planet);
+
+        let roads = build_roads(&planet, &planet_settings);
+        let links: Vec<(u32, u32, bool)> = roads.iter().map(|road| (road.from, road.to, road.is_shortcut)).collect();
+        // every link costs 40 but 0-3 and 2-3 at 80. The tree takes the cheap ones until 2 is
+        // joined, 1-2 would detour 80 over 0 which is more than 1.6 times 40. 0-3 and 2-3 have
+        // detours of 80, well within 1.6 times their own cost.
+        assert_eq!(links, vec![(0, 1, false), (0, 2, false), (1, 3, false), (1, 2, true)]);
+        for road in &roads {
+            let (from, to) = (&planet.settlements[road.from as usize], &planet.settlements[road.to as usize]);
+            assert_eq!((road.regions[0], *road.regions.last().unwrap()), (from.region, to.region));
+        }
+    }
+
+    #[test]
+    fn later_roads_join_the_earlier_ones() {
+        let planet = build_planet();
+        let planet_settings = test_planet::build_planet_settings(&planet);
+
+        let roads = build_roads(&planet, &planet_settings);
+        // 0-2 leaves along the 0-1 road, two discounted steps and two full ones
+        let shared: Vec<u32> =
+            roads[1].regions[1..].iter().copied().filter(|region| roads[0].regions.contains(region)).collect();
+        assert_eq!(shared, vec![get_region(2, 1), get_region(3, 1)]);
+        assert!((roads[1].cost - 28.0).abs() < 1e-4, "{}", roads[1].cost);
+
+        // without the discount the same road is a plain 40
+        let mut no_reuse = test_planet::build_planet_settings(&planet);
+        no_reuse.road_settings.reuse_factor = 1.0;
+        assert!((build_roads(&planet, &no_reuse)[1].cost - 40.0).abs() < 1e-4);
+    }
+}
@@ -0,0 +1,230 @@
+use std::collections::HashSet;
+
+use gamescript::models::{
+    continent::{Planet, PlanetSettings},
+    point::{calculate_wrapped_distance, Point16},
+    settlement::{Settlement, SettlementKind, SettlementSettings},
+};
+use rand::{rngs::StdRng, Rng, SeedableRng};
+
+use crate::{adjacency, naming_builder};
+
+pub const SETTLEMENT_SALT: u64 = 0x5eed_0006;
+
+// where a region sits in the hierarchy, numbered in the sorted walk
+struct RegionPlace {
+    continent: usize,
+    realm: u32,
+    province: u32,
+}
+
+// Scores every land region by how good a place it is to live and settles the best ones,
+// cities first, then towns and villages. A settlement keeps the spacing of its kind to every
+// settlement placed before it, so cities end up far apart and villages fill the gaps.
+pub fn build_settlements(planet: &Planet, planet_settings: &PlanetSettings) -> Vec<Settlement> {
+    let settings = &planet_settings.settlement_settings;
+    let keys = adjacency::get_sorted_continent_keys(&planet.continents);
+    let regions = adjacency::get_sorted_regions(&planet.continents, &keys);
+    let places = build_region_places(planet, &keys);
+
+    let scores = build_scores(planet, planet_settings);
+    let mut candidates: Vec<usize> = (0..regions.len()).filter(|&i| scores[i] > 0.0).collect();
+    candidates.sort_by(|&a, &b| scores[b].total_cmp(&scores[a]).then(a.cmp(&b)));
+
+    let mut rng = StdRng::seed_from_u64(planet_settings.seed ^ SETTLEMENT_SALT);
+    let generators = naming_builder::build_generators(&planet_settings.naming_settings);
+    let styles = &planet_settings.naming_settings.styles;
+    let mut used_names: HashSet<String> = HashSet::new();
+    let mut is_settled: Vec<bool> = vec![false; regions.len()];
+    let mut settlements: Vec<Settlement> = Vec::new();
+
+    for kind in SettlementKind::ALL {
+        let (count, spacing) = get_count_and_spacing(settings, kind);
+        let first = settlements.len();
+        for &region in &candidates {
+            if settlements.len() - first >= count {
+                break;
+            }
+            let site = &regions[region].site_point;
+            let is_far_enough = settlements.iter().all(|settlement| {
+                let position = Point16::new(settlement.position.0, settlement.position.1);
+                calculate_wrapped_distance(&position, site, planet.img_size.width) >= spacing
+            });
+            if is_settled[region] || !is_far_enough {
+                continue;
+            }
+
+            let place = &places[region];
+            let continent = &planet.continents[&keys[place.continent]];
+            let style = styles.iter().position(|style| style.name == continent.name_style);
+            let name = match style {
+                Some(style) => naming_builder::get_unique_name(
+                    &generators[style],
+                    &planet_settings.naming_settings,
+                    &mut rng,
+                    &mut used_names,
+                ),
+                None => format!("Settlement {}", settlements.len() + 1),
+            };
+
+            is_settled[region] = true;
+            settlements.push(Settlement {
+                name,
+                kind,
+                position: (site.x, site.y),
+                region: region as u32,
+                province: place.province,
+                realm: place.realm,
+                score: scores[region],
+            });
+        }
+    }
+
+    settlements
+}
+
+fn get_count_and_spacing(settings: &SettlementSettings, kind: SettlementKind) -> (usize, f32) {
+    match kind {
+        SettlementKind::City => (settings.cities as usize, settings.city_spacing),
+        SettlementKind::Town => (settings.towns as usize, settings.town_spacing),
+        SettlementKind::Village => (settings.villages as usize, settings.village_spacing),
+    }
+}
+
+fn build_region_places(planet: &Planet, keys: &[(u16, u16)]) -> Vec<RegionPlace> {
+    let mut places: Vec<RegionPlace> = Vec::new();
+    let (mut realm, mut province) = (0_u32, 0_u32);
+    for (continent, key) in keys.iter().enumerate() {
+        for rlm in &planet.continents[key].realms {
+            for pv in &rlm.provinces {
+                places.extend(pv.regions.iter().map(|_| RegionPlace { continent, realm, province }));
+                province += 1;
+            }
+            realm += 1;
+        }
+    }
+    places
+}
+
+// Suitability of every region in about 0..1, 0 for regions nobody can settle: the sea, lakes,
+// ice and land above `max_elevation`. Low, flat, fertile land near the coast and rivers scores best.
+fn build_scores(planet: &Planet, planet_settings: &PlanetSettings) -> Vec<f32> {
+    let settings = &planet_settings.settlement_settings;
+    let sea_level = planet_settings.tectonic_settings.sea_level;
+    let keys = adjacency::get_sorted_continent_keys(&planet.continents);
+    let regions = adjacency::get_sorted_regions(&planet.continents, &keys);
+    let (owners, regions_len) = adjacency::build_regions_owner_raster(&planet.img_size, &planet.continents, &keys);
+    let adjacency = adjacency::build_adjacency(&planet.img_size, &owners, regions_len, true);
+
+    let water = adjacency::build_region_water(planet, &regions, &adjacency, sea_level);
+    let (elevations, is_sea) = (&water.elevations, &water.is_sea);
+
+    let weight_sum = settings.elevation_weight
+        + settings.coast_weight
+        + settings.river_weight
+        + settings.flatness_weight
+        + settings.biome_weight;
+    let mut rng = StdRng::seed_from_u64(planet_settings.seed ^ SETTLEMENT_SALT ^ 1);
+
+    (0..regions_len)
+        .map(|i| {
+            // one draw per region keeps the jitter of a region independent of the others
+            let jitter: f32 = rng.gen();
+            let height = (elevations[i] - sea_level) / (settings.max_elevation - sea_level).max(f32::EPSILON);
+            let habitability = regions[i].biome.habitability();
+            // lakes and ice have no habitability
+            if is_sea[i] || height > 1.0 || habitability <= 0.0 || regions[i].pixels.is_empty() {
+                return 0.0;
+            }
+
+            let roughness = adjacency[i]
+                .iter()
+                .map(|&n| (elevations[n] - elevations[i]).abs())
+                .fold(0.0, f32::max);
+            let fade = |distance: u32, falloff: f32| 0.5_f32.powf(distance as f32 / falloff.max(f32::EPSILON));
+
+            let score = settings.elevation_weight * (1.0 - height)
+                + settings.coast_weight * fade(water.sea_distances[i], settings.coast_falloff)
+                + settings.river_weight * fade(water.fresh_water_distances[i], settings.river_falloff)
+                + settings.flatness_weight * (1.0 - (roughness / settings.ruggedness).min(1.0))
+                + settings.biome_weight * habitability;
+            score / weight_sum.max(f32::EPSILON) + settings.jitter * jitter
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_planet;
+    use gamescript::models::climate::Biome;
+
+    const GRID: (u16, u16) = (16, 8);
+
+    // grassland in 8 pixel cells with the sea in the two west columns, a lake, an ice cap
+    // and a mountain ridge above `max_elevation` cut out of it
+    fn build_planet() -> Planet {
+        test_planet::build_grid_planet(GRID, 8, |x, y| match (x, y) {
+            (0..=1, _) => (40, Biome::TemperateGrassland),
+            (5, 3) => (140, Biome::Lake),
+            (_, 0) => (140, Biome::Ice),
+            (12, _) => (240, Biome::TemperateGrassland),
+            _ => (120 + (x * 3 + y * 2) as u8, Biome::TemperateGrassland),
+        })
+    }
+
+    fn is_excluded(region: u32) -> bool {
+        let (x, y) = (region as u16 % GRID.0, region as u16 / GRID.0);
+        x <= 1 || (x, y) == (5, 3) || y == 0 || x == 12
+    }
+
+    #[test]
+    fn settlements_keep_the_spacing_of_their_kind() {
+        let planet = build_planet();
+        let mut planet_settings = test_planet::build_planet_settings(&planet);
+        planet_settings.settlement_settings = SettlementSettings {
+            cities: 3,
+            towns: 6,
+            villages: 20,
+            city_spacing: 40.0,
+            town_spacing: 20.0,
+            village_spacing: 10.0,
+            ..SettlementSettings::default()
+        };
+
+        let settlements = build_settlements(&planet, &planet_settings);
+        for kind in SettlementKind::ALL {
+            assert!(settlements.iter().any(|settlement| settlement.kind == kind), "{:?}", kind);
+        }
+        // every settlement keeps the spacing of its kind to the ones placed before it
+        for (i, settlement) in settlements.iter().enumerate() {
+            let (_, spacing) = get_count_and_spacing(&planet_settings.settlement_settings, settlement.kind);
+            let position = Point16::new(settlement.position.0, settlement.position.1);
+            for earlier in &settlements[..i] {
+                let other = Point16::new(earlier.position.0, earlier.position.1);
+                let distance = calculate_wrapped_distance(&position, &other, planet.img_size.width);
+                assert!(distance >= spacing, "{:?} {:?} {}", settlement.kind, earlier.kind, distance);
+            }
+        }
+    }
+
+    #[test]
+    fn sea_lakes_ice_and_high_land_stay_unsettled() {
+        // room for a village in every region, only the exclusions hold them back
+        let planet = build_planet();
+        let mut planet_settings = test_planet::build_planet_settings(&planet);
+        planet_settings.settlement_settings = SettlementSettings {
+            cities: 0,
+            towns: 0,
+            villages: 1000,
+            village_spacing: 1.0,
+            ..SettlementSettings::default()
+        };
+
+        let settlements = build_settlements(&planet, &planet_settings);
+        let mut settled: Vec<u32> = settlements.iter().map(|settlement| settlement.region).collect();
+        settled.sort_unstable();
+        let expected: Vec<u32> = (0..GRID.0 as u32 * GRID.1 as u32).filter(|&region| !is_excluded(region)).collect();
+        assert_eq!(settled, expected);
+    }
+}
@@ -0,0 +1,129 @@
+use std::{fmt::Write, fs};
+
+use gamescript::models::{
+    climate::Biome,
+    color::Color8,
+    continent::{MapLevel, Planet, PlanetSettings},
+    vector::SvgLayer,
+};
+use world::contour::Polygon;
+
+use crate::{
+    adjacency,
+    vector_builder::{self, Boundary},
+};
+
+const LAND_COLOR: Color8 = Color8 { r: 214, g: 202, b: 170 };
+
+// Writes the coastline and the outlines of every level as one svg, a group per layer from
+// the coastline at the bottom up to the continents. Hidden layers are left out.
+pub fn build_planet_svg(planet: &Planet, planet_settings: &PlanetSettings, svg_name: &str) {
+    let settings = &planet_settings.vector_settings;
+    let scale = settings.scale;
+    let width = planet.img_size.width as f32 * scale;
+    let height = planet.img_size.height as f32 * scale;
+    let sea = Biome::Ocean.color();
+
+    let mut svg = String::new();
+    writeln!(
+        svg,
+        r#"<svg xmlns="http://www.w3.org/2000/svg" width="{w}" height="{h}" viewBox="0 0 {w} {h}">"#,
+        w = width,
+        h = height
+    )
+    .unwrap();
+    writeln!(svg, r#"<rect id="sea" width="{}" height="{}" fill="{}"/>"#, width, height, to_hex(&sea)).unwrap();
+
+    if settings.coastline.visible {
+        let coastline = vector_builder::build_coastline(
+            planet,
+            planet_settings.tectonic_settings.sea_level,
+            settings.tolerance,
+        );
+        open_group(&mut svg, "coastline", &settings.coastline);
+        write_path(&mut svg, "coastline-0", &coastline, &LAND_COLOR, scale);
+        svg.push_str("</g>\n");
+    }
+
+    for level in MapLevel::ALL {
+        let layer = get_layer(planet_settings, level);
+        if !layer.visible {
+            continue;
+        }
+        let boundaries = vector_builder::build_boundaries(planet, level, settings.tolerance);
+        let fills = get_fill_colors(planet, planet_settings, level, &boundaries);
+
+        open_group(&mut svg, &format!("{}s", level.name()), layer);
+        for (boundary, fill) in boundaries.iter().zip(&fills) {
+            let id = format!("{}-{}", level.name(), boundary.id);
+            write_path(&mut svg, &id, &boundary.polygons, fill, scale);
+        }
+        svg.push_str("</g>\n");
+    }
+
+    svg.push_str("</svg>\n");
+    fs::write(svg_name, svg).unwrap();
+}
+
+pub fn get_layer(planet_settings: &PlanetSettings, level: MapLevel) -> &SvgLayer {
+    let settings = &planet_settings.vector_settings;
+    match level {
+        MapLevel::Region => &settings.regions,
+        MapLevel::Province => &settings.provinces,
+        MapLevel::Realm => &settings.realms,
+        MapLevel::Continent => &settings.continents,
+    }
+}
+
+// regions show their biome, the other levels a colour hashed from their id
+fn get_fill_colors(planet: &Planet, planet_settings: &PlanetSettings, level: MapLevel, boundaries: &[Boundary]) -> Vec<Color8> {
+    match level {
+        MapLevel::Region => {
+            let keys = adjacency::get_sorted_continent_keys(&planet.continents);
+            let regions = adjacency::get_sorted_regions(&planet.continents, &keys);
+            boundaries.iter().map(|boundary| regions[boundary.id as usize].biome.color()).collect()
+        }
+        _ => {
+            let seed = planet_settings.seed as u32 ^ level as u32;
+            boundaries
+                .iter()
+                .map(|boundary| {
+                    let channel = |i: i32| 60 + (world::noise::hash(boundary.id as i32, i, seed) * 160.0) as u8;
+                    Color8::new(channel(0), channel(1), channel(2))
+                })
+                .collect()
+        }
+    }
+}
+
+fn open_group(svg: &mut String, id: &str, layer: &SvgLayer) {
+    writeln!(
+        svg,
+        r#"<g id="{}" fill-opacity="{}" stroke="{}" stroke-width="{}" stroke-linejoin="round" fill-rule="evenodd">"#,
+        id,
+        layer.fill_opacity,
+        to_hex(&Color8::new(layer.stroke[0], layer.stroke[1], layer.stroke[2])),
+        layer.stroke_width
+    )
+    .unwrap();
+}
+
+// all outlines of a cell go into one path so holes cut out with the even-odd rule
+fn write_path(svg: &mut String, id: &str, polygons: &[Polygon], fill: &Color8, scale: f32) {
+    if polygons.is_empty() {
+        return;
+    }
+    let mut d = String::new();
+    for polygon in polygons {
+        for (i, p) in polygon.iter().enumerate() {
+            let command = if i == 0 { 'M' } else { 'L' };
+            write!(d, "{}{:.2} {:.2}", command, p.0 * scale, p.1 * scale).unwrap();
+        }
+        d.push('Z');
+    }
+    writeln!(svg, r#"<path id="{}" fill="{}" d="{}"/>"#, id, to_hex(fill), d).unwrap();
+}
+
+fn to_hex(color: &Color8) -> String {
+    format!("#{:02x}{:02x}{:02x}", color.r, color.g, color.b)
+}
@@ -0,0 +1,378 @@
+use gamescript::models::{
+    continent::{Continent, PlanetSettings},
+    point::{denormalize_u8, normalize_u8},
+    tectonics::{BoundaryType, PlateBoundary, PlateType, TectonicSettings},
+};
+use rand::{rngs::StdRng, seq::SliceRandom, SeedableRng};
+use std::{
+    cmp::Ordering,
+    collections::{BinaryHeap, HashMap},
+};
+use world::image_gradient;
+
+use crate::{adjacency, continent_builder};
+
+pub const PLATE_TYPE_SALT: u64 = 0x5eed_000d;
+
+struct BorderStats {
+    convergence_sum: f32,
+    shear_sum: f32,
+    length: u32,
+}
+
+struct Frontier {
+    distance: f32,
+    region: usize,
+    effect: f32,
+}
+
+impl PartialEq for Frontier {
+    fn eq(&self, other: &Self) -> bool {
+        self.distance == other.distance
+    }
+}
+
+impl Eq for Frontier {}
+
+impl PartialOrd for Frontier {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for Frontier {
+    // reversed, so the BinaryHeap pops the closest region first
+    fn cmp(&self, other: &Self) -> Ordering {
+        other
+            .distance
+            .total_cmp(&self.distance)
+            .then_with(|| other.region.cmp(&self.region))
+    }
+}
+
+// Marks plates as continental in random order until they cover `land_fraction` of the
+// planet, the rest is ocean floor. Elevations are then picked from the range of each type.
+pub fn assign_plate_types(
+    continents: &mut HashMap<(u16, u16), Continent>,
+    planet_settings: &PlanetSettings,
+) {
+    let plate_settings = &planet_settings.plate_settings;
+    let mut keys = adjacency::get_sorted_continent_keys(continents);
+    let mut rng = StdRng::seed_from_u64(planet_settings.seed ^ PLATE_TYPE_SALT);
+    keys.shuffle(&mut rng);
+
+    let get_area = |continent: &Continent| -> usize {
+        continent
+            .realms
+            .iter()
+            .flat_map(|rlm| &rlm.provinces)
+            .flat_map(|pv| &pv.regions)
+            .map(|rg| rg.pixels.len())
+            .sum()
+    };
+    let total_area: usize = continents.values().map(get_area).sum();
+    let land_area_target = total_area as f32 * plate_settings.land_fraction;
+
+    let mut land_area: usize = 0;
+    for key in &keys {
+        if let Some(continent) = continents.get_mut(key) {
+            continent.plate_type = if (land_area as f32) < land_area_target {
+                land_area += get_area(continent);
+                PlateType::Continental
+            } else {
+                PlateType::Oceanic
+            };
+            continent.elevation =
+                continent_builder::get_random_tectonic_elevation(continent.plate_type, plate_settings, &mut rng);
+        }
+    }
+}
+
+// Turns every plate direction into a velocity, classifies the borders between plates and
+// raises or lowers the regions along them, fading out with the distance to the border.
+pub fn apply_plate_boundaries(
+    continents: &mut HashMap<(u16, u16), Continent>,
+    planet_settings: &PlanetSettings,
+) -> Vec<PlateBoundary> {
+    let tectonic_settings = &planet_settings.tectonic_settings;
+    let keys = adjacency::get_sorted_continent_keys(continents);
+    let velocities: Vec<(f32, f32)> = keys
+        .iter()
+        .map(|key| get_plate_velocity(continents[key].plate_movement_direction))
+        .collect();
+
+    let mut sites: Vec<(f32, f32)> = Vec::new();
+    let mut region_plates: Vec<usize> = Vec::new();
+    for (p, key) in keys.iter().enumerate() {
+        for rlm in &continents[key].realms {
+            for pv in &rlm.provinces {
+                for rg in &pv.regions {
+                    sites.push((rg.site_point.x as f32, rg.site_point.y as f32));
+                    region_plates.push(p);
+                }
+            }
+        }
+    }
+
+    let (owners, regions_len) =
+        adjacency::build_regions_owner_raster(&planet_settings.img_size, continents, &keys);
+    let adjacency = adjacency::build_adjacency(&planet_settings.img_size, &owners, regions_len, true);
+
+    // accumulate relative motion over every pair of regions touching across a border
+    let width = planet_settings.img_size.width as f32;
+    let mut borders: HashMap<(usize, usize), BorderStats> = HashMap::new();
+    for a in 0..regions_len {
+        for &b in &adjacency[a] {
+            let (plate_a, plate_b) = (region_plates[a], region_plates[b]);
+            if b < a || plate_a == plate_b {
+                continue;
+            }
+
+            let (convergence, shear) = get_relative_motion(
+                velocities[plate_a], velocities[plate_b], get_site_offset(sites[a], sites[b], width),
+            );
+            let key = (plate_a.min(plate_b), plate_a.max(plate_b));
+            let stats = borders.entry(key).or_insert(BorderStats {
+                convergence_sum: 0.0,
+                shear_sum: 0.0,
+                length: 0,
+            });
+            // convergence is symmetric, shear sign depends on the side, so only its size is kept
+            stats.convergence_sum += convergence;
+            stats.shear_sum += shear.abs();
+            stats.length += 1;
+        }
+    }
+
+    let mut border_keys: Vec<(usize, usize)> = borders.keys().copied().collect();
+    border_keys.sort_unstable();
+
+    let plate_boundaries: Vec<PlateBoundary> = border_keys
+        .iter()
+        .map(|&(plate_a, plate_b)| {
+            let stats = &borders[&(plate_a, plate_b)];
+            let convergence = stats.convergence_sum / stats.length as f32;
+            let shear = stats.shear_sum / stats.length as f32;
+            PlateBoundary {
+                plates: (keys[plate_a], keys[plate_b]),
+                boundary_type: classify_boundary(convergence, shear, tectonic_settings),
+                convergence,
+                shear,
+                length: stats.length,
+            }
+        })
+        .collect();
+    let boundary_indexes: HashMap<(usize, usize), usize> = border_keys
+        .iter()
+        .enumerate()
+        .map(|(i, &border_key)| (border_key, i))
+        .collect();
+
+    // every region on a border starts a front carrying the effect of its boundary
+    let mut heap: BinaryHeap<Frontier> = BinaryHeap::new();
+    for a in 0..regions_len {
+        let plate_a = region_plates[a];
+        let mut effect: f32 = 0.0;
+        for &b in &adjacency[a] {
+            let plate_b = region_plates[b];
+            if plate_a == plate_b {
+                continue;
+            }
+
+            let boundary = &plate_boundaries[boundary_indexes[&(plate_a.min(plate_b), plate_a.max(plate_b))]];
+            let border_effect = get_boundary_effect(
+                boundary,
+                &continents[&keys[plate_a]],
+                &continents[&keys[plate_b]],
+                tectonic_settings,
+            );
+            if border_effect.abs() > effect.abs() {
+                effect = border_effect;
+            }
+        }
+        if effect != 0.0 {
+            heap.push(Frontier { distance: 0.0, region: a, effect });
+        }
+    }
+
+    let deltas = spread_boundary_effects(heap, &adjacency, &sites, width, tectonic_settings);
+
+    let mut i: usize = 0;
+    for key in &keys {
+        if let Some(continent) = continents.get_mut(key) {
+            for rlm in &mut continent.realms {
+                for pv in &mut rlm.provinces {
+                    for rg in &mut pv.regions {
+                        let new_value = normalize_u8(rg.grey_value as f64)
+                            + (deltas[i] * tectonic_settings.blend) as f64;
+                        rg.grey_value = denormalize_u8(new_value.clamp(0.0, 1.0));
+                        i += 1;
+                    }
+                }
+            }
+        }
+    }
+
+    continent_builder::calculate_average_grey_values(continents);
+
+    plate_boundaries
+}
+
+// 0 degrees points east and 90 points north, image y grows downwards
+pub fn get_plate_velocity(plate_movement_direction: u8) -> (f32, f32) {
+    let radians = image_gradient::get_degrees_by_index(plate_movement_direction).to_radians();
+    (radians.cos(), -radians.sin())
+}
+
+// from site a to site b the shorter way around the map, which wraps horizontally
+fn get_site_offset(site_a: (f32, f32), site_b: (f32, f32), width: f32) -> (f32, f32) {
+    let dx = site_b.0 - site_a.0;
+    let dx = if dx > width / 2.0 {
+        dx - width
+    } else if dx < -width / 2.0 {
+        dx + width
+    } else {
+        dx
+    };
+    (dx, site_b.1 - site_a.1)
+}
+
+// returns the closing speed along the line between both sites and the sliding speed across it,
+// `offset` points from site a to site b
+fn get_relative_motion(velocity_a: (f32, f32), velocity_b: (f32, f32), offset: (f32, f32)) -> (f32, f32) {
+    let (nx, ny) = offset;
+    let length = (nx * nx + ny * ny).sqrt().max(f32::EPSILON);
+    let (nx, ny) = (nx / length, ny / length);
+
+    let (rx, ry) = (velocity_a.0 - velocity_b.0, velocity_a.1 - velocity_b.1);
+    (rx * nx + ry * ny, rx * ny - ry * nx)
+}
+
+fn classify_boundary(convergence: f32, shear: f32, tectonic_settings: &TectonicSettings) -> BoundaryType {
+    if shear > convergence.abs() * tectonic_settings.transform_ratio {
+        BoundaryType::Transform
+    } else if convergence > 0.0 {
+        BoundaryType::Convergent
+    } else {
+        BoundaryType::Divergent
+    }
+}
+
+fn get_boundary_effect(
+    boundary: &PlateBoundary,
+    plate: &Continent,
+    other_plate: &Continent,
+    tectonic_settings: &TectonicSettings,
+) -> f32 {
+    // relative speeds go up to 2.0 for plates moving head on
+    let intensity = (boundary.convergence.abs() / 2.0).clamp(0.0, 1.0);
+
+    let effect = match boundary.boundary_type {
+        BoundaryType::Convergent => match (plate.plate_type, other_plate.plate_type) {
+            (PlateType::Continental, PlateType::Continental) => tectonic_settings.mountain_height,
+            // the oceanic plate dives under the continent, leaving a trench off a coastal range
+            (PlateType::Oceanic, PlateType::Continental) => -tectonic_settings.trench_depth,
+            (PlateType::Continental, PlateType::Oceanic) => tectonic_settings.coastal_range_height,
+            // the lower, denser oceanic plate subducts and raises an island arc on the other one
+            (PlateType::Oceanic, PlateType::Oceanic) => {
+                if plate.elevation <= other_plate.elevation {
+                    -tectonic_settings.trench_depth
+                } else {
+                    tectonic_settings.island_arc_height
+                }
+            }
+        },
+        BoundaryType::Divergent => match plate.plate_type {
+            PlateType::Oceanic => tectonic_settings.ridge_height,
+            PlateType::Continental => -tectonic_settings.rift_depth,
+        },
+        BoundaryType::Transform => 0.0,
+    };
+
+    effect * intensity
+}
+
+// multi source dijkstra over the region graph, every region takes the effect of its nearest border
+fn spread_boundary_effects(
+    mut heap: BinaryHeap<Frontier>,
+    adjacency: &[Vec<usize>],
+    sites: &[(f32, f32)],
+    width: f32,
+    tectonic_settings: &TectonicSettings,
+) -> Vec<f32> {
+    let mut distances: Vec<f32> = vec![f32::INFINITY; adjacency.len()];
+    let mut deltas: Vec<f32> = vec![0.0; adjacency.len()];
+
+    while let Some(Frontier { distance, region, effect }) = heap.pop() {
+        if distance >= distances[region] {
+            continue;
+        }
+        distances[region] = distance;
+        deltas[region] = effect * get_falloff(distance, tectonic_settings);
+
+        for &neighbour in &adjacency[region] {
+            let (dx, dy) = get_site_offset(sites[region], sites[neighbour], width);
+            let next_distance = distance + dx.hypot(dy);
+            if next_distance < distances[neighbour] && next_distance < tectonic_settings.falloff_distance {
+                heap.push(Frontier { distance: next_distance, region: neighbour, effect });
+            }
+        }
+    }
+
+    deltas
+}
+
+fn get_falloff(distance: f32, tectonic_settings: &TectonicSettings) -> f32 {
+    let t = (1.0 - distance / tectonic_settings.falloff_distance).max(0.0);
+    t.powf(tectonic_settings.falloff_power)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use gamescript::models::{
+        continent::{Province, Realm, Region},
+        point::Point16,
+    };
+
+    // three plates as 4 pixel wide strips of one region each, the west and the east one
+    // touch only across the seam
+    fn build_strip_plates(directions: [u8; 3]) -> HashMap<(u16, u16), Continent> {
+        let mut continents: HashMap<(u16, u16), Continent> = HashMap::new();
+        for (i, direction) in directions.into_iter().enumerate() {
+            let x0 = i as u16 * 4;
+            let site = Point16::new(x0 + 2, 2);
+            let mut rg = Region::new(Point16::new(site.x, site.y));
+            rg.grey_value = 128;
+            rg.pixels = (0..4).flat_map(|y| (x0..x0 + 4).map(move |x| (x, y))).collect();
+            let mut pv = Province::new(Point16::new(i as u16, 0), Point16::new(site.x, site.y));
+            pv.regions.push(rg);
+            let mut rlm = Realm::new(Point16::new(i as u16, 0), Point16::new(site.x, site.y));
+            rlm.provinces.push(pv);
+            let mut continent = Continent::new(Point16::new(i as u16, 0), site, direction, 0.5);
+            continent.realms.push(rlm);
+            continents.insert((i as u16, 0), continent);
+        }
+        continents
+    }
+
+    #[test]
+    fn plates_meeting_across_the_seam_share_a_boundary() {
+        // the west plate moves west and the east plate east, so they close in over the seam
+        let mut continents = build_strip_plates([8, 4, 0]);
+        let planet_settings = crate::create_planet_settings(12, 4, 4, 4, 4, 4);
+
+        let boundaries = apply_plate_boundaries(&mut continents, &planet_settings);
+        let seam = boundaries.iter().find(|boundary| boundary.plates == ((0, 0), (2, 0))).unwrap();
+        assert_eq!(seam.boundary_type, BoundaryType::Convergent);
+        assert!((seam.convergence - 2.0).abs() < 1e-4, "{}", seam.convergence);
+        assert_eq!(seam.length, 1);
+    }
+
+    #[test]
+    fn site_offset_takes_the_shorter_way() {
+        assert_eq!(get_site_offset((2.0, 2.0), (10.0, 3.0), 12.0), (-4.0, 1.0));
+        assert_eq!(get_site_offset((10.0, 2.0), (2.0, 2.0), 12.0), (4.0, 0.0));
+        assert_eq!(get_site_offset((2.0, 2.0), (6.0, 2.0), 12.0), (4.0, 0.0));
+    }
+}
@@ -0,0 +1,47 @@
+use std::collections::HashMap;
+
+use gamescript::models::{
+    climate::Biome,
+    continent::{Continent, Planet, PlanetSettings, Province, Realm, Region},
+    point::{Point16, Size16},
+};
+
+// Planet for the builder tests: one continent and realm holding a grid of square provinces
+// with one region each, numbered row by row like the sorted walk numbers them.
+// `describe` gives the grey value and the biome of the cell at a grid coord.
+pub fn build_grid_planet(grid: (u16, u16), cell: u16, describe: impl Fn(u16, u16) -> (u8, Biome)) -> Planet {
+    let mut rlm = Realm::new(Point16::default(), Point16::default());
+    for y in 0..grid.1 {
+        for x in 0..grid.0 {
+            let site = Point16::new(x * cell + cell / 2, y * cell + cell / 2);
+            let (grey_value, biome) = describe(x, y);
+            let mut rg = Region::new(Point16::new(site.x, site.y));
+            rg.grey_value = grey_value;
+            rg.biome = biome;
+            rg.pixels = (y * cell..(y + 1) * cell).flat_map(|py| (x * cell..(x + 1) * cell).map(move |px| (px, py))).collect();
+            let mut pv = Province::new(Point16::new(x, y), site);
+            pv.biome_summary.dominant = biome;
+            pv.regions.push(rg);
+            rlm.provinces.push(pv);
+        }
+    }
+    let mut continent = Continent::default();
+    continent.realms.push(rlm);
+
+    Planet {
+        img_size: Size16::new(grid.0 * cell, grid.1 * cell),
+        continents: HashMap::from([((0, 0), continent)]),
+        plate_boundaries: Vec::new(),
+        heightmap: None,
+        hydrology: None,
+        politics: None,
+        settlements: Vec::new(),
+        roads: Vec::new(),
+    }
+}
+
+// default settings for a planet of the size, the hierarchy grids play no part in the tests
+pub fn build_planet_settings(planet: &Planet) -> PlanetSettings {
+    let Size16 { width, height } = planet.img_size;
+    crate::create_planet_settings(width, height, width, width, width, width)
+}
@@ -0,0 +1,66 @@
+use gamescript::models::continent::{MapLevel, Planet};
+use world::contour::{self, Polygon};
+
+use crate::adjacency::{self, NO_OWNER};
+
+// Outline of a single cell at some level, ids follow `adjacency::build_owner_raster`.
+pub struct Boundary {
+    pub id: u32,
+    pub polygons: Vec<Polygon>,
+}
+
+// Traces the outline of every cell at the level. Each cell is traced within its own
+// bounding box, so the whole level costs about one pass over the map.
+pub fn build_boundaries(planet: &Planet, level: MapLevel, tolerance: f32) -> Vec<Boundary> {
+    let keys = adjacency::get_sorted_continent_keys(&planet.continents);
+    let (owners, cells_len) = adjacency::build_owner_raster(&planet.img_size, &planet.continents, &keys, level);
+    let width = planet.img_size.width as i32;
+    let height = planet.img_size.height as i32;
+
+    let mut bounds: Vec<(i32, i32, i32, i32)> = vec![(i32::MAX, i32::MAX, i32::MIN, i32::MIN); cells_len];
+    for y in 0..height {
+        for x in 0..width {
+            let owner = owners[(y * width + x) as usize];
+            if owner == NO_OWNER {
+                continue;
+            }
+            let b = &mut bounds[owner as usize];
+            *b = (b.0.min(x), b.1.min(y), b.2.max(x), b.3.max(y));
+        }
+    }
+
+    bounds
+        .iter()
+        .enumerate()
+        .filter(|(_, b)| b.0 <= b.2)
+        .map(|(id, b)| {
+            let polygons = contour::trace_contours((b.0, b.1), (b.2, b.3), 0.5, |x, y| {
+                (owners[(y * width + x) as usize] == id as u32) as u8 as f32
+            });
+            Boundary {
+                id: id as u32,
+                polygons: simplify_polygons(polygons, tolerance),
+            }
+        })
+        .collect()
+}
+
+// Land outlines where the heightmap, or the region values without one, cross the sea level.
+pub fn build_coastline(planet: &Planet, sea_level: f32, tolerance: f32) -> Vec<Polygon> {
+    let width = planet.img_size.width as i32;
+    let height = planet.img_size.height as i32;
+    let elevations = planet.get_pixel_elevations();
+    let polygons = contour::trace_contours((0, 0), (width - 1, height - 1), sea_level, |x, y| {
+        elevations[(y * width + x) as usize]
+    });
+    simplify_polygons(polygons, tolerance)
+}
+
+// drops outlines that simplify down to less than a triangle
+fn simplify_polygons(polygons: Vec<Polygon>, tolerance: f32) -> Vec<Polygon> {
+    polygons
+        .iter()
+        .map(|polygon| contour::simplify_polygon(polygon, tolerance))
+        .filter(|polygon| polygon.len() >= 3)
+        .collect()
+}
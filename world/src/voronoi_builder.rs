@@ -1,14 +1,19 @@
 extern crate image;
-use rand::Rng;
+use rand::{rngs::StdRng, Rng, SeedableRng};
 use voronoice::{BoundingBox, Point, Voronoi, VoronoiBuilder};
 use gamescript::models::{continent::Region, point::{Size16, Point16, try_map_min_max_points}};
 
-pub fn generate_scattered_sites(img_size: &Size16, len: usize) -> Vec<Point> {
-    let mut rng = rand::thread_rng();
+use crate::progress::ProgressBar;
+
+pub const REGION_SITES_SALT: u64 = 0x5eed_000b;
+
+pub fn generate_scattered_sites(img_size: &Size16, len: usize, seed: u64) -> Vec<Point> {
+    let mut rng = StdRng::seed_from_u64(seed ^ REGION_SITES_SALT);
     let x_range = rand::distributions::Uniform::new(0, img_size.width);
     let y_range = rand::distributions::Uniform::new(0, img_size.height);
 
     let mut sites: Vec<Point> = Vec::with_capacity(len); // Use a Vec to store the sites
+    let mut progress_bar = ProgressBar::new("sites", len);
 
     while sites.len() < len {
         let x = rng.sample(x_range) as f64;
@@ -22,6 +27,7 @@ pub fn generate_scattered_sites(img_size: &Size16, len: usize) -> Vec<Point> {
 
         if !is_coincident {
             sites.push(new_site);
+            progress_bar.inc();
         }
     }
 
@@ -39,8 +45,10 @@ pub fn build_voronoi_and_apply_site_pixels_and_corners(img_size: &Size16, region
     let voronoi = build(img_size, sites);
 
     let mut last_site_index = 0;
+    let mut progress_bar = ProgressBar::new("pixels", img_size.width as usize - 1);
 
     for x in 0..img_size.width - 1 {
+        progress_bar.inc();
         for y in 0..img_size.height - 1 {
             let site_index = get_cell_index(&voronoi, last_site_index, x, y);
             last_site_index = site_index;